@@ -0,0 +1,38 @@
+use chill_json::FuzzyJsonParser;
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+
+/// Builds an LLM-style response of roughly `size` bytes: prose before a fenced block,
+/// single quoted values, non-ASCII text and a truncated tail, so every repair path is hit.
+fn messy_llm_response(size: usize) -> String {
+    let mut out = String::from("Here's the JSON you asked for:\n```json\n{\"items\": [");
+    let mut i = 0;
+    while out.len() < size {
+        out.push_str(&format!(
+            "{{\"id\": {i}, \"name\": 'item {i}', \"city\": \"São Paulo\", \"tags\": [\"a\", \"b\"], \"ok\": true}},\n"
+        ));
+        i += 1;
+    }
+    out.push_str("{\"id\": ");
+    out.push_str(&i.to_string());
+    out.push_str(", \"name\": \"trunc");
+    out
+}
+
+fn bench_repair(c: &mut Criterion) {
+    let parser = FuzzyJsonParser::new();
+
+    let mut group = c.benchmark_group("parse_value");
+    // Throughput per byte should stay flat as the input grows if repair is linear.
+    for size in [10_000, 50_000, 200_000] {
+        let input = messy_llm_response(size);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &input, |b, input| {
+            b.iter(|| parser.parse_value(black_box(input)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_repair);
+criterion_main!(benches);
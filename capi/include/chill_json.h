@@ -0,0 +1,136 @@
+#ifndef CHILL_JSON_H
+#define CHILL_JSON_H
+
+/* Generated by cbindgen from capi/src/lib.rs, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// What NaN and Infinity turn into, see `NonFiniteNumbers`.
+typedef enum ChillJsonNonFiniteNumbers {
+  CHILL_JSON_NON_FINITE_NUMBERS_NULL = 0,
+  CHILL_JSON_NON_FINITE_NUMBERS_STRING,
+} ChillJsonNonFiniteNumbers;
+
+// Kind of the last error, mirroring the `FuzzyJsonError` variants.
+typedef enum ChillJsonErrorKind {
+  // The last call succeeded.
+  CHILL_JSON_ERROR_KIND_NONE = 0,
+  CHILL_JSON_ERROR_KIND_UNEXPECTED_CHARACTER,
+  CHILL_JSON_ERROR_KIND_UNBALANCED_CLOSE,
+  CHILL_JSON_ERROR_KIND_UNEXPECTED_END,
+  CHILL_JSON_ERROR_KIND_AMBIGUOUS_DOCUMENTS,
+  CHILL_JSON_ERROR_KIND_INVALID_ESCAPE,
+  CHILL_JSON_ERROR_KIND_INVALID_NUMBER,
+  CHILL_JSON_ERROR_KIND_EXCEEDED_REPAIR_ATTEMPTS,
+  CHILL_JSON_ERROR_KIND_INPUT_TOO_LARGE,
+  CHILL_JSON_ERROR_KIND_NESTING_TOO_DEEP,
+  CHILL_JSON_ERROR_KIND_STRING_TOO_LONG,
+  CHILL_JSON_ERROR_KIND_TOO_MANY_ENTRIES,
+  CHILL_JSON_ERROR_KIND_EXCEEDED_STEP_BUDGET,
+  CHILL_JSON_ERROR_KIND_EXCEEDED_TIME_BUDGET,
+  CHILL_JSON_ERROR_KIND_STRICT_MODE_VIOLATION,
+  CHILL_JSON_ERROR_KIND_DESERIALIZATION_MISMATCH,
+  CHILL_JSON_ERROR_KIND_REPAIR_FAILED,
+  CHILL_JSON_ERROR_KIND_JSON_ERROR,
+  // A `NULL` pointer or input that isn't UTF-8 was passed in.
+  CHILL_JSON_ERROR_KIND_INVALID_ARGUMENT,
+  // The parser panicked; this is a bug worth reporting.
+  CHILL_JSON_ERROR_KIND_PANIC,
+} ChillJsonErrorKind;
+
+// Opaque parser handle.
+typedef struct ChillJsonParser ChillJsonParser;
+
+// Parser options, see `ParserOptions`. Start from `chill_json_default_options()`.
+typedef struct ChillJsonOptions {
+  bool auto_repair;
+  bool allow_trailing_commas;
+  bool allow_comments;
+  bool allow_single_quotes;
+  bool allow_unquoted_keys;
+  // Characters unquoted keys may contain on top of an identifier's, as a NUL-terminated
+  // UTF-8 string. `NULL` keeps the default, `-.`.
+  const char *unquoted_key_chars;
+  bool allow_bareword_values;
+  size_t max_repair_attempts;
+  bool strict_mode;
+  bool aggressive_truncation_repair;
+  bool allow_backtick_strings;
+  bool allow_python_literals;
+  enum ChillJsonNonFiniteNumbers non_finite_numbers;
+  // JSON Schema the value is validated against and fixed up to match, as NUL-terminated
+  // JSON text, or `NULL` for none.
+  const char *schema;
+  // Limits for untrusted input, 0 for none.
+  size_t max_input_bytes;
+  size_t max_depth;
+  size_t max_string_length;
+  size_t max_entries;
+  size_t max_steps;
+  uint64_t max_duration_ms;
+} ChillJsonOptions;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Options `chill_json_parser_new(NULL)` uses.
+struct ChillJsonOptions chill_json_default_options(void);
+
+// Creates a parser, with the default options if `options` is `NULL`.
+// Free it with `chill_json_parser_free`.
+//
+// Returns `NULL` when a string option isn't UTF-8 or the schema isn't JSON; the
+// `chill_json_last_error_*` functions then describe why.
+//
+// # Safety
+//
+// `options` must be `NULL` or point to a valid `ChillJsonOptions`, whose strings are
+// `NULL` or NUL-terminated.
+struct ChillJsonParser *chill_json_parser_new(const struct ChillJsonOptions *options);
+
+// Frees a parser created by `chill_json_parser_new`. `NULL` is ignored.
+//
+// # Safety
+//
+// `parser` must be `NULL` or a parser that hasn't been freed yet.
+void chill_json_parser_free(struct ChillJsonParser *parser);
+
+// Repairs the `len` bytes of UTF-8 at `input` (no NUL terminator needed) and returns the
+// value as a compact, NUL-terminated JSON string, to be freed with `chill_json_string_free`.
+//
+// Returns `NULL` on failure; the `chill_json_last_error_*` functions then describe why.
+//
+// # Safety
+//
+// `parser` must be a live parser and `input` must point to `len` readable bytes.
+char *chill_json_repair(const struct ChillJsonParser *parser, const char *input, size_t len);
+
+// Kind of the error the last call on this thread failed with, or
+// `CHILL_JSON_ERROR_KIND_NONE`.
+enum ChillJsonErrorKind chill_json_last_error_kind(void);
+
+// Byte offset into the input where the last error happened, or -1 when the error has no
+// position (or there was no error).
+int64_t chill_json_last_error_position(void);
+
+// Message of the last error as a new string to be freed with `chill_json_string_free`,
+// or `NULL` when the last call succeeded.
+char *chill_json_last_error_message(void);
+
+// Frees a string returned by this library. `NULL` is ignored.
+//
+// # Safety
+//
+// `string` must be `NULL` or a string from this library that hasn't been freed yet.
+void chill_json_string_free(char *string);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CHILL_JSON_H */
@@ -0,0 +1,387 @@
+//! C API over `FuzzyJsonParser`, see `include/chill_json.h`.
+//!
+//! Errors are reported like `errno`: a failing call returns `NULL` and records the error
+//! for the calling thread, to be read with the `chill_json_last_error_*` functions.
+
+use chill_json::{FuzzyJsonError, FuzzyJsonParser, NonFiniteNumbers, ParserOptions};
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::time::Duration;
+
+/// Kind of the last error, mirroring the `FuzzyJsonError` variants.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChillJsonErrorKind {
+    /// The last call succeeded.
+    None = 0,
+    UnexpectedCharacter,
+    UnbalancedClose,
+    UnexpectedEnd,
+    AmbiguousDocuments,
+    InvalidEscape,
+    InvalidNumber,
+    ExceededRepairAttempts,
+    InputTooLarge,
+    NestingTooDeep,
+    StringTooLong,
+    TooManyEntries,
+    ExceededStepBudget,
+    ExceededTimeBudget,
+    StrictModeViolation,
+    DeserializationMismatch,
+    RepairFailed,
+    JsonError,
+    /// A `NULL` pointer or input that isn't UTF-8 was passed in.
+    InvalidArgument,
+    /// The parser panicked; this is a bug worth reporting.
+    Panic,
+}
+
+/// What NaN and Infinity turn into, see `NonFiniteNumbers`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChillJsonNonFiniteNumbers {
+    Null = 0,
+    String,
+}
+
+/// Parser options, see `ParserOptions`. Start from `chill_json_default_options()`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ChillJsonOptions {
+    pub auto_repair: bool,
+    pub allow_trailing_commas: bool,
+    pub allow_comments: bool,
+    pub allow_single_quotes: bool,
+    pub allow_unquoted_keys: bool,
+    /// Characters unquoted keys may contain on top of an identifier's, as a NUL-terminated
+    /// UTF-8 string. `NULL` keeps the default, `-.`.
+    pub unquoted_key_chars: *const c_char,
+    pub allow_bareword_values: bool,
+    pub max_repair_attempts: usize,
+    pub strict_mode: bool,
+    pub aggressive_truncation_repair: bool,
+    pub allow_backtick_strings: bool,
+    pub allow_python_literals: bool,
+    pub non_finite_numbers: ChillJsonNonFiniteNumbers,
+    /// JSON Schema the value is validated against and fixed up to match, as NUL-terminated
+    /// JSON text, or `NULL` for none.
+    pub schema: *const c_char,
+    /// Limits for untrusted input, 0 for none.
+    pub max_input_bytes: usize,
+    pub max_depth: usize,
+    pub max_string_length: usize,
+    pub max_entries: usize,
+    pub max_steps: usize,
+    pub max_duration_ms: u64,
+}
+
+/// The `ParserOptions` for `options`, or why its strings don't make sense.
+///
+/// # Safety
+///
+/// The strings in `options` must be `NULL` or NUL-terminated.
+unsafe fn parser_options(options: &ChillJsonOptions) -> Result<ParserOptions, String> {
+    // SAFETY: the caller passes NULL or NUL-terminated strings
+    let unquoted_key_chars = unsafe { c_str(options.unquoted_key_chars, "unquoted_key_chars") }?;
+    let schema = unsafe { c_str(options.schema, "schema") }?
+        .map(|schema| serde_json::from_str(schema).map_err(|e| format!("schema: {e}")))
+        .transpose()?;
+    let defaults = ParserOptions::default();
+    Ok(ParserOptions {
+        auto_repair: options.auto_repair,
+        allow_trailing_commas: options.allow_trailing_commas,
+        allow_comments: options.allow_comments,
+        allow_single_quotes: options.allow_single_quotes,
+        allow_unquoted_keys: options.allow_unquoted_keys,
+        unquoted_key_chars: unquoted_key_chars.map_or(defaults.unquoted_key_chars, str::to_string),
+        allow_bareword_values: options.allow_bareword_values,
+        max_repair_attempts: options.max_repair_attempts,
+        strict_mode: options.strict_mode,
+        aggressive_truncation_repair: options.aggressive_truncation_repair,
+        allow_backtick_strings: options.allow_backtick_strings,
+        allow_python_literals: options.allow_python_literals,
+        non_finite_numbers: match options.non_finite_numbers {
+            ChillJsonNonFiniteNumbers::Null => NonFiniteNumbers::Null,
+            ChillJsonNonFiniteNumbers::String => NonFiniteNumbers::String,
+        },
+        schema,
+        max_input_bytes: limit(options.max_input_bytes),
+        max_depth: limit(options.max_depth),
+        max_string_length: limit(options.max_string_length),
+        max_entries: limit(options.max_entries),
+        max_steps: limit(options.max_steps),
+        max_duration: (options.max_duration_ms > 0)
+            .then(|| Duration::from_millis(options.max_duration_ms)),
+    })
+}
+
+/// The UTF-8 string at `string`, `None` for `NULL`.
+///
+/// # Safety
+///
+/// `string` must be `NULL` or NUL-terminated.
+unsafe fn c_str<'a>(string: *const c_char, name: &str) -> Result<Option<&'a str>, String> {
+    if string.is_null() {
+        return Ok(None);
+    }
+    // SAFETY: the caller passes a NUL-terminated string
+    let string = unsafe { CStr::from_ptr(string) };
+    string
+        .to_str()
+        .map(Some)
+        .map_err(|e| format!("{name} isn't UTF-8: {e}"))
+}
+
+fn limit(max: usize) -> Option<usize> {
+    (max > 0).then_some(max)
+}
+
+/// Opaque parser handle.
+pub struct ChillJsonParser {
+    inner: FuzzyJsonParser,
+}
+
+struct LastError {
+    kind: ChillJsonErrorKind,
+    position: Option<usize>,
+    message: String,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<LastError>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(kind: ChillJsonErrorKind, position: Option<usize>, message: String) {
+    LAST_ERROR.with(|last| {
+        *last.borrow_mut() = Some(LastError {
+            kind,
+            position,
+            message,
+        })
+    });
+}
+
+fn clear_last_error() {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+}
+
+fn record_error(error: FuzzyJsonError) {
+    let kind = match &error {
+        FuzzyJsonError::UnexpectedCharacter { .. } => ChillJsonErrorKind::UnexpectedCharacter,
+        FuzzyJsonError::UnbalancedClose { .. } => ChillJsonErrorKind::UnbalancedClose,
+        FuzzyJsonError::UnexpectedEnd { .. } => ChillJsonErrorKind::UnexpectedEnd,
+        FuzzyJsonError::AmbiguousDocuments { .. } => ChillJsonErrorKind::AmbiguousDocuments,
+        FuzzyJsonError::InvalidEscape { .. } => ChillJsonErrorKind::InvalidEscape,
+        FuzzyJsonError::InvalidNumber { .. } => ChillJsonErrorKind::InvalidNumber,
+        FuzzyJsonError::ExceededRepairAttempts { .. } => ChillJsonErrorKind::ExceededRepairAttempts,
+        FuzzyJsonError::InputTooLarge { .. } => ChillJsonErrorKind::InputTooLarge,
+        FuzzyJsonError::NestingTooDeep { .. } => ChillJsonErrorKind::NestingTooDeep,
+        FuzzyJsonError::StringTooLong { .. } => ChillJsonErrorKind::StringTooLong,
+        FuzzyJsonError::TooManyEntries { .. } => ChillJsonErrorKind::TooManyEntries,
+        FuzzyJsonError::ExceededStepBudget { .. } => ChillJsonErrorKind::ExceededStepBudget,
+        FuzzyJsonError::ExceededTimeBudget { .. } => ChillJsonErrorKind::ExceededTimeBudget,
+        FuzzyJsonError::StrictModeViolation { .. } => ChillJsonErrorKind::StrictModeViolation,
+        FuzzyJsonError::DeserializationMismatch { .. } => {
+            ChillJsonErrorKind::DeserializationMismatch
+        }
+        FuzzyJsonError::RepairFailed(_) => ChillJsonErrorKind::RepairFailed,
+        FuzzyJsonError::JsonError(_) => ChillJsonErrorKind::JsonError,
+    };
+    let position = error.location().map(|location| location.offset);
+    set_last_error(kind, position, error.to_string());
+}
+
+/// Options `chill_json_parser_new(NULL)` uses.
+#[unsafe(no_mangle)]
+pub extern "C" fn chill_json_default_options() -> ChillJsonOptions {
+    let defaults = ParserOptions::default();
+    ChillJsonOptions {
+        auto_repair: defaults.auto_repair,
+        allow_trailing_commas: defaults.allow_trailing_commas,
+        allow_comments: defaults.allow_comments,
+        allow_single_quotes: defaults.allow_single_quotes,
+        allow_unquoted_keys: defaults.allow_unquoted_keys,
+        unquoted_key_chars: ptr::null(),
+        allow_bareword_values: defaults.allow_bareword_values,
+        max_repair_attempts: defaults.max_repair_attempts,
+        strict_mode: defaults.strict_mode,
+        aggressive_truncation_repair: defaults.aggressive_truncation_repair,
+        allow_backtick_strings: defaults.allow_backtick_strings,
+        allow_python_literals: defaults.allow_python_literals,
+        non_finite_numbers: match defaults.non_finite_numbers {
+            NonFiniteNumbers::Null => ChillJsonNonFiniteNumbers::Null,
+            NonFiniteNumbers::String => ChillJsonNonFiniteNumbers::String,
+        },
+        schema: ptr::null(),
+        max_input_bytes: defaults.max_input_bytes.unwrap_or(0),
+        max_depth: defaults.max_depth.unwrap_or(0),
+        max_string_length: defaults.max_string_length.unwrap_or(0),
+        max_entries: defaults.max_entries.unwrap_or(0),
+        max_steps: defaults.max_steps.unwrap_or(0),
+        max_duration_ms: defaults
+            .max_duration
+            .map_or(0, |duration| duration.as_millis() as u64),
+    }
+}
+
+/// Creates a parser, with the default options if `options` is `NULL`.
+/// Free it with `chill_json_parser_free`.
+///
+/// Returns `NULL` when a string option isn't UTF-8 or the schema isn't JSON; the
+/// `chill_json_last_error_*` functions then describe why.
+///
+/// # Safety
+///
+/// `options` must be `NULL` or point to a valid `ChillJsonOptions`, whose strings are
+/// `NULL` or NUL-terminated.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chill_json_parser_new(
+    options: *const ChillJsonOptions,
+) -> *mut ChillJsonParser {
+    clear_last_error();
+    // SAFETY: the caller passes NULL or a valid pointer
+    let options = match unsafe { options.as_ref() } {
+        // SAFETY: the caller passes NULL or NUL-terminated strings
+        Some(options) => match unsafe { parser_options(options) } {
+            Ok(options) => options,
+            Err(message) => {
+                set_last_error(ChillJsonErrorKind::InvalidArgument, None, message);
+                return ptr::null_mut();
+            }
+        },
+        None => ParserOptions::default(),
+    };
+    Box::into_raw(Box::new(ChillJsonParser {
+        inner: FuzzyJsonParser::with_options(options),
+    }))
+}
+
+/// Frees a parser created by `chill_json_parser_new`. `NULL` is ignored.
+///
+/// # Safety
+///
+/// `parser` must be `NULL` or a parser that hasn't been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chill_json_parser_free(parser: *mut ChillJsonParser) {
+    if !parser.is_null() {
+        // SAFETY: the parser was allocated by `chill_json_parser_new`
+        drop(unsafe { Box::from_raw(parser) });
+    }
+}
+
+/// Repairs the `len` bytes of UTF-8 at `input` (no NUL terminator needed) and returns the
+/// value as a compact, NUL-terminated JSON string, to be freed with `chill_json_string_free`.
+///
+/// Returns `NULL` on failure; the `chill_json_last_error_*` functions then describe why.
+///
+/// # Safety
+///
+/// `parser` must be a live parser and `input` must point to `len` readable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chill_json_repair(
+    parser: *const ChillJsonParser,
+    input: *const c_char,
+    len: usize,
+) -> *mut c_char {
+    clear_last_error();
+    // SAFETY: the caller passes a live parser or NULL
+    let Some(parser) = (unsafe { parser.as_ref() }) else {
+        set_last_error(
+            ChillJsonErrorKind::InvalidArgument,
+            None,
+            "parser is NULL".to_string(),
+        );
+        return ptr::null_mut();
+    };
+    if input.is_null() {
+        set_last_error(
+            ChillJsonErrorKind::InvalidArgument,
+            None,
+            "input is NULL".to_string(),
+        );
+        return ptr::null_mut();
+    }
+    // SAFETY: the caller guarantees `len` readable bytes at `input`
+    let bytes = unsafe { std::slice::from_raw_parts(input.cast::<u8>(), len) };
+    let text = match std::str::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(e) => {
+            set_last_error(
+                ChillJsonErrorKind::InvalidArgument,
+                Some(e.valid_up_to()),
+                e.to_string(),
+            );
+            return ptr::null_mut();
+        }
+    };
+
+    match panic::catch_unwind(AssertUnwindSafe(|| parser.inner.parse_value(text))) {
+        Ok(Ok(value)) => into_c_string(value.to_string()),
+        Ok(Err(error)) => {
+            record_error(error);
+            ptr::null_mut()
+        }
+        Err(_) => {
+            set_last_error(
+                ChillJsonErrorKind::Panic,
+                None,
+                "the parser panicked".to_string(),
+            );
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Kind of the error the last call on this thread failed with, or
+/// `CHILL_JSON_ERROR_KIND_NONE`.
+#[unsafe(no_mangle)]
+pub extern "C" fn chill_json_last_error_kind() -> ChillJsonErrorKind {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ChillJsonErrorKind::None, |error| error.kind)
+    })
+}
+
+/// Byte offset into the input where the last error happened, or -1 when the error has no
+/// position (or there was no error).
+#[unsafe(no_mangle)]
+pub extern "C" fn chill_json_last_error_position() -> i64 {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .and_then(|error| error.position)
+            .map_or(-1, |position| position as i64)
+    })
+}
+
+/// Message of the last error as a new string to be freed with `chill_json_string_free`,
+/// or `NULL` when the last call succeeded.
+#[unsafe(no_mangle)]
+pub extern "C" fn chill_json_last_error_message() -> *mut c_char {
+    LAST_ERROR.with(|last| match last.borrow().as_ref() {
+        Some(error) => into_c_string(error.message.clone()),
+        None => ptr::null_mut(),
+    })
+}
+
+/// Frees a string returned by this library. `NULL` is ignored.
+///
+/// # Safety
+///
+/// `string` must be `NULL` or a string from this library that hasn't been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chill_json_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        // SAFETY: the string was allocated by `into_c_string`
+        drop(unsafe { CString::from_raw(string) });
+    }
+}
+
+fn into_c_string(string: String) -> *mut c_char {
+    // JSON escapes NUL and the messages don't contain any, but better safe than sorry
+    CString::new(string.replace('\0', "\\u0000")).map_or(ptr::null_mut(), CString::into_raw)
+}
@@ -0,0 +1,42 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Compiles `tests/test.c` against the generated header and the cdylib, then runs it.
+#[test]
+fn c_test_program() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // the test binary lives in target/<profile>/deps, next to which cargo put the library
+    let target_dir = std::env::current_exe()
+        .unwrap()
+        .parent()
+        .and_then(|deps| deps.parent())
+        .unwrap()
+        .to_path_buf();
+    let program = target_dir.join("chill_json_c_test");
+
+    let compiler = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(compiler)
+        .arg(manifest_dir.join("tests/test.c"))
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg("-L")
+        .arg(&target_dir)
+        .arg("-lchill_json_capi")
+        .arg("-o")
+        .arg(&program)
+        .status()
+        .expect("failed to run the C compiler");
+    assert!(status.success(), "compiling tests/test.c failed");
+
+    let output = Command::new(&program)
+        .env("LD_LIBRARY_PATH", &target_dir)
+        .env("DYLD_LIBRARY_PATH", &target_dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "C test program failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "ok");
+}
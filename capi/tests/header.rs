@@ -0,0 +1,15 @@
+/// The committed header must match the one the build script generates from `src/lib.rs`.
+/// After changing the API, copy it over with `UPDATE_HEADER=1 cargo test`.
+#[test]
+fn header_is_up_to_date() {
+    let generated = include_str!(concat!(env!("OUT_DIR"), "/chill_json.h"));
+    let committed_path = concat!(env!("CARGO_MANIFEST_DIR"), "/include/chill_json.h");
+    if std::env::var_os("UPDATE_HEADER").is_some() {
+        std::fs::write(committed_path, generated).unwrap();
+    }
+    let committed = std::fs::read_to_string(committed_path).unwrap();
+    assert!(
+        committed == generated,
+        "include/chill_json.h is out of date, run `UPDATE_HEADER=1 cargo test`"
+    );
+}
@@ -0,0 +1,76 @@
+#include <assert.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "chill_json.h"
+
+static char *repair(const struct ChillJsonParser *parser, const char *input) {
+  return chill_json_repair(parser, input, strlen(input));
+}
+
+int main(void) {
+  struct ChillJsonParser *parser = chill_json_parser_new(NULL);
+
+  char *json = repair(parser, "Sure:\n```json\n{'name': 'test', \"items\": [1, 2,");
+  assert(json != NULL);
+  assert(strcmp(json, "{\"name\":\"test\",\"items\":[1,2]}") == 0);
+  assert(chill_json_last_error_kind() == CHILL_JSON_ERROR_KIND_NONE);
+  assert(chill_json_last_error_message() == NULL);
+  chill_json_string_free(json);
+
+  /* the input doesn't need a NUL terminator */
+  json = chill_json_repair(parser, "[1, 2]garbage", 6);
+  assert(json != NULL && strcmp(json, "[1,2]") == 0);
+  chill_json_string_free(json);
+
+  const char *invalid_utf8 = "{\"a\": \"\xff\"}";
+  assert(repair(parser, invalid_utf8) == NULL);
+  assert(chill_json_last_error_kind() == CHILL_JSON_ERROR_KIND_INVALID_ARGUMENT);
+  assert(chill_json_last_error_position() == 7);
+  chill_json_parser_free(parser);
+
+  struct ChillJsonOptions options = chill_json_default_options();
+  options.strict_mode = true;
+  parser = chill_json_parser_new(&options);
+  assert(repair(parser, "{\"name\": \"test\", \"value\":") == NULL);
+  assert(chill_json_last_error_kind() == CHILL_JSON_ERROR_KIND_STRICT_MODE_VIOLATION);
+  assert(chill_json_last_error_position() == 25);
+  char *message = chill_json_last_error_message();
+  assert(message != NULL && strstr(message, "truncation_repair") != NULL);
+  chill_json_string_free(message);
+  chill_json_parser_free(parser);
+
+  options = chill_json_default_options();
+  options.max_depth = 1;
+  parser = chill_json_parser_new(&options);
+  assert(repair(parser, "[[1]]") == NULL);
+  assert(chill_json_last_error_kind() == CHILL_JSON_ERROR_KIND_NESTING_TOO_DEEP);
+  chill_json_parser_free(parser);
+
+  options = chill_json_default_options();
+  options.allow_trailing_commas = false;
+  options.allow_single_quotes = false;
+  parser = chill_json_parser_new(&options);
+  assert(repair(parser, "[1, 2,]") == NULL);
+  assert(repair(parser, "{'a': 1}") == NULL);
+  chill_json_parser_free(parser);
+
+  options = chill_json_default_options();
+  options.unquoted_key_chars = "@";
+  options.schema = "{\"properties\": {\"unit\": {\"enum\": [\"celsius\"]}}}";
+  parser = chill_json_parser_new(&options);
+  json = repair(parser, "{@type: 1, unit: 'Celsius'}");
+  assert(json != NULL && strcmp(json, "{\"@type\":1,\"unit\":\"celsius\"}") == 0);
+  chill_json_string_free(json);
+  assert(repair(parser, "{a.b: 1}") == NULL);
+  chill_json_parser_free(parser);
+
+  options.schema = "{\"properties\":";
+  assert(chill_json_parser_new(&options) == NULL);
+  assert(chill_json_last_error_kind() == CHILL_JSON_ERROR_KIND_INVALID_ARGUMENT);
+
+  chill_json_parser_free(NULL);
+  chill_json_string_free(NULL);
+  puts("ok");
+  return 0;
+}
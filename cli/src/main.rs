@@ -0,0 +1,355 @@
+use chill_json::{FuzzyJsonParser, NonFiniteNumbers, ParserOptions, RepairReport};
+use clap::{Args, Parser};
+use serde_json::Value;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+/// Repairs the JSON in messy text, such as LLM responses, and writes it to stdout.
+#[derive(Parser, Debug)]
+#[command(
+    name = "chill-json",
+    version,
+    after_help = "Exit status: 0 on success, 1 if --check found input that needed repair, \
+                  2 if some input could not be repaired."
+)]
+struct Cli {
+    /// Files to read, stdin when none are given or for `-`
+    files: Vec<PathBuf>,
+
+    /// Extract every JSON document embedded in the input
+    #[arg(long)]
+    all: bool,
+
+    /// Treat every line of the input as its own record (NDJSON), one output line each
+    #[arg(long, conflicts_with_all = ["all", "pretty"])]
+    ndjson: bool,
+
+    /// Pretty-print the output (the default)
+    #[arg(long, conflicts_with = "compact")]
+    pretty: bool,
+
+    /// Print each document on a single line
+    #[arg(long)]
+    compact: bool,
+
+    /// Print the repair strategies that fired for each document to stderr
+    #[arg(long)]
+    report: bool,
+
+    /// Write nothing to stdout, exit with status 1 if any input needed repair
+    #[arg(long)]
+    check: bool,
+
+    #[command(flatten)]
+    options: OptionFlags,
+}
+
+/// One flag per `ParserOptions` field. `--flag` alone means `--flag=true`.
+#[derive(Args, Debug)]
+#[command(next_help_heading = "Parser options")]
+struct OptionFlags {
+    /// Repair input that isn't valid JSON [default: true]
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+    auto_repair: Option<bool>,
+
+    /// Accept trailing commas [default: true]
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+    allow_trailing_commas: Option<bool>,
+
+    /// Skip `//`, `/* */` and `#` comments [default: true]
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+    allow_comments: Option<bool>,
+
+    /// Accept single-quoted strings [default: true]
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+    allow_single_quotes: Option<bool>,
+
+    /// Accept unquoted object keys [default: true]
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+    allow_unquoted_keys: Option<bool>,
+
+    /// Characters unquoted keys may contain besides identifier ones [default: -.]
+    #[arg(long, value_name = "CHARS")]
+    unquoted_key_chars: Option<String>,
+
+    /// Accept unquoted string values, like `{status: active}` [default: false]
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+    allow_bareword_values: Option<bool>,
+
+    /// Give up on a document that needs more repairs than this [default: 1500]
+    #[arg(long, value_name = "N")]
+    max_repair_attempts: Option<usize>,
+
+    /// Refuse repairs that drop input or invent values [default: false]
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+    strict_mode: Option<bool>,
+
+    /// Close the scopes truncated input left open [default: true]
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+    aggressive_truncation_repair: Option<bool>,
+
+    /// Accept `backtick` quoted strings [default: false]
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+    allow_backtick_strings: Option<bool>,
+
+    /// Accept Python's True/False/None, tuples and r''/u''/triple-quoted strings [default: false]
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+    allow_python_literals: Option<bool>,
+
+    /// What NaN and Infinity turn into, `null` or `string` [default: null]
+    #[arg(long, value_name = "POLICY")]
+    non_finite_numbers: Option<NonFiniteNumbers>,
+
+    /// JSON Schema file to validate and fix up every document against
+    #[arg(long, value_name = "FILE")]
+    schema: Option<PathBuf>,
+
+    /// Refuse documents longer than this many bytes
+    #[arg(long, value_name = "N")]
+    max_input_bytes: Option<usize>,
+
+    /// Refuse objects and arrays nested deeper than this
+    #[arg(long, value_name = "N")]
+    max_depth: Option<usize>,
+
+    /// Refuse strings longer than this many bytes
+    #[arg(long, value_name = "N")]
+    max_string_length: Option<usize>,
+
+    /// Refuse objects and arrays with more entries than this
+    #[arg(long, value_name = "N")]
+    max_entries: Option<usize>,
+
+    /// Give up on a document after this many steps
+    #[arg(long, value_name = "N")]
+    max_steps: Option<usize>,
+
+    /// Give up on a document after this many milliseconds
+    #[arg(long, value_name = "MS")]
+    max_duration: Option<u64>,
+}
+
+impl OptionFlags {
+    fn parser_options(&self) -> Result<ParserOptions, String> {
+        let mut options = ParserOptions::default();
+        let flags = [
+            (self.auto_repair, &mut options.auto_repair),
+            (
+                self.allow_trailing_commas,
+                &mut options.allow_trailing_commas,
+            ),
+            (self.allow_comments, &mut options.allow_comments),
+            (self.allow_single_quotes, &mut options.allow_single_quotes),
+            (self.allow_unquoted_keys, &mut options.allow_unquoted_keys),
+            (
+                self.allow_bareword_values,
+                &mut options.allow_bareword_values,
+            ),
+            (self.strict_mode, &mut options.strict_mode),
+            (
+                self.aggressive_truncation_repair,
+                &mut options.aggressive_truncation_repair,
+            ),
+            (
+                self.allow_backtick_strings,
+                &mut options.allow_backtick_strings,
+            ),
+            (
+                self.allow_python_literals,
+                &mut options.allow_python_literals,
+            ),
+        ];
+        for (flag, field) in flags {
+            if let Some(flag) = flag {
+                *field = flag;
+            }
+        }
+        if let Some(max) = self.max_repair_attempts {
+            options.max_repair_attempts = max;
+        }
+        if let Some(chars) = &self.unquoted_key_chars {
+            options.unquoted_key_chars = chars.clone();
+        }
+        if let Some(policy) = self.non_finite_numbers {
+            options.non_finite_numbers = policy;
+        }
+        options.max_input_bytes = self.max_input_bytes;
+        options.max_depth = self.max_depth;
+        options.max_string_length = self.max_string_length;
+        options.max_entries = self.max_entries;
+        options.max_steps = self.max_steps;
+        options.max_duration = self.max_duration.map(Duration::from_millis);
+        if let Some(path) = &self.schema {
+            let schema =
+                std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+            options.schema = Some(
+                serde_json::from_str(&schema).map_err(|e| format!("{}: {e}", path.display()))?,
+            );
+        }
+        Ok(options)
+    }
+}
+
+/// Outcome of the whole run, turned into the exit status.
+#[derive(Default)]
+struct Summary {
+    needed_repair: bool,
+    failed: bool,
+}
+
+struct Run<'a> {
+    cli: &'a Cli,
+    parser: FuzzyJsonParser,
+    out: BufWriter<io::StdoutLock<'static>>,
+    summary: Summary,
+}
+
+impl Run<'_> {
+    fn input(&mut self, name: &str, mut input: impl BufRead) -> io::Result<()> {
+        if self.cli.ndjson {
+            // each record is written as soon as its line is read, for `tail -f | chill-json`
+            for (index, line) in input.lines().enumerate() {
+                let line = line?;
+                if !line.trim().is_empty() {
+                    self.document(&format!("{name}:{}", index + 1), &line)?;
+                }
+            }
+            return Ok(());
+        }
+
+        let mut text = String::new();
+        input.read_to_string(&mut text)?;
+        if self.cli.all {
+            let documents = self.parser.extract_all(&text);
+            if documents.is_empty() {
+                eprintln!("{name}: error: no JSON document found");
+                self.summary.failed = true;
+            }
+            for (index, document) in documents.iter().enumerate() {
+                self.repaired(
+                    &format!("{name}[{index}]"),
+                    &document.value,
+                    &document.report,
+                )?;
+            }
+        } else {
+            self.document(name, &text)?;
+        }
+        Ok(())
+    }
+
+    fn document(&mut self, label: &str, text: &str) -> io::Result<()> {
+        match self.parser.parse_with_report(text) {
+            Ok((value, report)) => self.repaired(label, &value, &report)?,
+            Err(e) => {
+                eprintln!("{label}: error: {e}");
+                self.summary.failed = true;
+            }
+        }
+        Ok(())
+    }
+
+    fn repaired(&mut self, label: &str, value: &Value, report: &RepairReport) -> io::Result<()> {
+        self.summarize(label, report);
+        if !self.cli.check {
+            self.write(value)?;
+        }
+        Ok(())
+    }
+
+    fn summarize(&mut self, label: &str, report: &RepairReport) {
+        if !report.already_valid {
+            self.summary.needed_repair = true;
+        }
+        if self.cli.report {
+            if report.already_valid {
+                eprintln!("{label}: valid");
+            } else {
+                // handlers fix some things (like trailing commas) without any strategy firing
+                let strategies: Vec<_> = report.strategies().collect();
+                let strategies = match strategies.is_empty() {
+                    true => String::new(),
+                    false => format!(": {}", strategies.join(", ")),
+                };
+                let lossy = if report.is_lossy() { " (lossy)" } else { "" };
+                eprintln!("{label}: repaired{strategies}{lossy}");
+            }
+        } else if self.cli.check && !report.already_valid {
+            eprintln!("{label}: needs repair");
+        }
+    }
+
+    fn write(&mut self, value: &Value) -> io::Result<()> {
+        if self.cli.compact || self.cli.ndjson {
+            serde_json::to_writer(&mut self.out, value)?;
+        } else {
+            serde_json::to_writer_pretty(&mut self.out, value)?;
+        }
+        writeln!(self.out)?;
+        if self.cli.ndjson {
+            self.out.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Opens `path`, stdin for `-`, along with the name its errors are reported under.
+fn open_input(path: &PathBuf) -> io::Result<(String, Box<dyn BufRead>)> {
+    if path.as_os_str() == "-" {
+        Ok(("<stdin>".to_string(), Box::new(io::stdin().lock())))
+    } else {
+        let file = BufReader::new(File::open(path)?);
+        Ok((path.display().to_string(), Box::new(file)))
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let options = match cli.options.parser_options() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let files = if cli.files.is_empty() {
+        vec![PathBuf::from("-")]
+    } else {
+        cli.files.clone()
+    };
+    let mut run = Run {
+        cli: &cli,
+        parser: FuzzyJsonParser::with_options(options),
+        out: BufWriter::new(io::stdout().lock()),
+        summary: Summary::default(),
+    };
+
+    for path in &files {
+        let result = open_input(path).and_then(|(name, input)| run.input(&name, input));
+        if let Err(e) = result {
+            if e.kind() == io::ErrorKind::BrokenPipe {
+                return ExitCode::SUCCESS;
+            }
+            eprintln!("{}: error: {e}", path.display());
+            run.summary.failed = true;
+        }
+    }
+    if let Err(e) = run.out.flush()
+        && e.kind() != io::ErrorKind::BrokenPipe
+    {
+        eprintln!("error: {e}");
+        run.summary.failed = true;
+    }
+
+    if run.summary.failed {
+        ExitCode::from(2)
+    } else if cli.check && run.summary.needed_repair {
+        ExitCode::from(1)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -0,0 +1,136 @@
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Output, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+fn run(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_chill-json"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+#[test]
+fn repairs_stdin() {
+    let output = run(
+        &["--compact"],
+        "Sure! ```json\n{'name': 'test', \"items\": [1, 2,",
+    );
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "{\"name\":\"test\",\"items\":[1,2]}\n");
+}
+
+#[test]
+fn pretty_output_and_report() {
+    let output = run(&["--report"], "{\"a\": [1,");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "{\n  \"a\": [\n    1\n  ]\n}\n");
+    assert_eq!(stderr(&output), "<stdin>: repaired: truncation_repair\n");
+    assert_eq!(stdout(&run(&["--pretty"], "{\"a\": [1,")), stdout(&output));
+}
+
+#[test]
+fn extracts_all_documents() {
+    let output = run(
+        &["--all", "--compact"],
+        "first {\"a\": 1} then [2, 3] and {\"b\": 'x",
+    );
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "{\"a\":1}\n[2,3]\n{\"b\":\"x\"}\n");
+}
+
+#[test]
+fn ndjson_records() {
+    let output = run(&["--ndjson"], "{\"a\": 1}\n\n{'b': 2\nnot json\n");
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(stdout(&output), "{\"a\":1}\n{\"b\":2}\n");
+    assert!(stderr(&output).starts_with("<stdin>:4: error:"));
+}
+
+#[test]
+fn ndjson_records_are_written_as_they_arrive() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_chill-json"))
+        .arg("--ndjson")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let stdout = BufReader::new(child.stdout.take().unwrap());
+    let (sender, lines) = mpsc::channel();
+    thread::spawn(move || stdout.lines().for_each(|line| sender.send(line).unwrap()));
+
+    // stdin stays open, like `tail -f app.log | chill-json --ndjson`
+    for (record, expected) in [("{'a': 1\n", "{\"a\":1}"), ("[1, 2,\n", "[1,2]")] {
+        stdin.write_all(record.as_bytes()).unwrap();
+        stdin.flush().unwrap();
+        let line = lines.recv_timeout(Duration::from_secs(10));
+        if line.is_err() {
+            child.kill().unwrap();
+        }
+        assert_eq!(line.unwrap().unwrap(), expected);
+    }
+    drop(stdin);
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn check_mode() {
+    let valid = run(&["--check"], "{\"a\": 1}");
+    assert_eq!(valid.status.code(), Some(0));
+    assert!(stdout(&valid).is_empty());
+
+    let broken = run(&["--check"], "{\"a\": 1,");
+    assert_eq!(broken.status.code(), Some(1));
+    assert!(stdout(&broken).is_empty());
+    assert_eq!(stderr(&broken), "<stdin>: needs repair\n");
+}
+
+#[test]
+fn parser_option_flags() {
+    let input = "{\"a\": 1, // note\n \"b\": 2}";
+    assert!(run(&[], input).status.success());
+    assert_eq!(
+        run(&["--allow-comments=false"], input).status.code(),
+        Some(2)
+    );
+
+    assert!(run(&[], "[1, 'a',]").status.success());
+    assert_eq!(
+        run(&["--allow-trailing-commas=false"], "[1, 2,]")
+            .status
+            .code(),
+        Some(2)
+    );
+    assert_eq!(
+        run(&["--allow-single-quotes=false"], "['a']").status.code(),
+        Some(2)
+    );
+
+    let strict = run(&["--strict-mode"], "{\"a\": 1, \"b\":");
+    assert_eq!(strict.status.code(), Some(2));
+    assert!(stderr(&strict).contains("Strict mode refused"));
+
+    let deep = run(&["--max-depth", "1"], "[[1]]");
+    assert_eq!(deep.status.code(), Some(2));
+    assert!(stderr(&deep).contains("depth limit of 1"));
+}
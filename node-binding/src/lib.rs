@@ -1,72 +1,208 @@
-use chill_json::FuzzyJsonParser;
+use chill_json::{FuzzyJsonError, FuzzyJsonParser, ParserOptions};
 use neon::prelude::*;
+use neon::types::JsBigInt;
 use serde_json::Value;
+use std::time::Duration;
 
-fn to_js_value<'a>(cx: &'a mut FunctionContext, value: &Value) -> Handle<'a, JsValue> {
-    match value {
-        Value::Null => {
-            let val = cx.null().upcast();
-            val
-        }
-        Value::Bool(b) => {
-            let val = cx.boolean(*b).upcast();
+/// Largest integer a JS number holds exactly, anything beyond becomes a `BigInt`.
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
 
-            val
-        }
+fn to_js_value<'a, C: Context<'a>>(cx: &mut C, value: &Value) -> JsResult<'a, JsValue> {
+    match value {
+        Value::Null => Ok(cx.null().upcast()),
+        Value::Bool(b) => Ok(cx.boolean(*b).upcast()),
         Value::Number(n) => {
             let val = if let Some(i) = n.as_i64() {
-                cx.number(i as f64).upcast()
+                if i.unsigned_abs() <= MAX_SAFE_INTEGER {
+                    cx.number(i as f64).upcast()
+                } else {
+                    JsBigInt::from_i64(cx, i).upcast()
+                }
             } else if let Some(u) = n.as_u64() {
-                cx.number(u as f64).upcast()
+                JsBigInt::from_u64(cx, u).upcast()
             } else if let Some(f) = n.as_f64() {
                 cx.number(f).upcast()
             } else {
                 cx.null().upcast()
             };
-            val
-        }
-        Value::String(s) => {
-            let val = cx.string(s).upcast();
-            val
+            Ok(val)
         }
+        Value::String(s) => Ok(cx.string(s).upcast()),
         Value::Array(arr) => {
-            let js_array: Handle<'a, JsArray> = cx.empty_array();
-            let mut vec = vec![];
+            let js_array = JsArray::new(cx, arr.len());
             for (i, elem) in arr.iter().enumerate() {
-                {
-                    let js_value = to_js_value(cx, elem);
-
-                    vec.push(js_value);
-                    // let mut prop_opt = js_array.prop(cx, i as u32);
-                    // prop_opt.set(js_value).unwrap();
-                }
+                let js_value = to_js_value(cx, elem)?;
+                js_array.set(cx, i as u32, js_value)?;
             }
-            js_array.upcast()
+            Ok(js_array.upcast())
         }
         Value::Object(map) => {
             let js_object = cx.empty_object();
             for (k, v) in map.iter() {
-                let js_val = to_js_value(cx, v);
-                js_object.set(cx, k.as_str(), js_val).unwrap();
+                let js_val = to_js_value(cx, v)?;
+                js_object.set(cx, k.as_str(), js_val)?;
             }
-            js_object.upcast()
+            Ok(js_object.upcast())
+        }
+    }
+}
+
+/// Reads the optional options object, camelCase keys mapping to `ParserOptions` fields.
+fn parser_from_options(cx: &mut FunctionContext, index: usize) -> NeonResult<FuzzyJsonParser> {
+    let mut options = ParserOptions::default();
+    let Some(js_options) = cx.argument_opt(index) else {
+        return Ok(FuzzyJsonParser::with_options(options));
+    };
+    if js_options.is_a::<JsUndefined, _>(cx) || js_options.is_a::<JsNull, _>(cx) {
+        return Ok(FuzzyJsonParser::with_options(options));
+    }
+    let js_options = js_options.downcast_or_throw::<JsObject, _>(cx)?;
+
+    let flags: [(&str, &mut bool); 10] = [
+        ("autoRepair", &mut options.auto_repair),
+        ("allowTrailingCommas", &mut options.allow_trailing_commas),
+        ("allowComments", &mut options.allow_comments),
+        ("allowSingleQuotes", &mut options.allow_single_quotes),
+        ("allowUnquotedKeys", &mut options.allow_unquoted_keys),
+        ("allowBarewordValues", &mut options.allow_bareword_values),
+        ("strictMode", &mut options.strict_mode),
+        (
+            "aggressiveTruncationRepair",
+            &mut options.aggressive_truncation_repair,
+        ),
+        ("allowBacktickStrings", &mut options.allow_backtick_strings),
+        ("allowPythonLiterals", &mut options.allow_python_literals),
+    ];
+    for (key, field) in flags {
+        if let Some(flag) = js_options.get_opt::<JsBoolean, _, _>(cx, key)? {
+            *field = flag.value(cx);
+        }
+    }
+    if let Some(max) = js_options.get_opt::<JsNumber, _, _>(cx, "maxRepairAttempts")? {
+        options.max_repair_attempts = max.value(cx) as usize;
+    }
+    let limits: [(&str, &mut Option<usize>); 5] = [
+        ("maxInputBytes", &mut options.max_input_bytes),
+        ("maxDepth", &mut options.max_depth),
+        ("maxStringLength", &mut options.max_string_length),
+        ("maxEntries", &mut options.max_entries),
+        ("maxSteps", &mut options.max_steps),
+    ];
+    for (key, field) in limits {
+        if let Some(max) = js_options.get_opt::<JsNumber, _, _>(cx, key)? {
+            *field = Some(max.value(cx) as usize);
+        }
+    }
+    if let Some(ms) = js_options.get_opt::<JsNumber, _, _>(cx, "maxDurationMs")? {
+        options.max_duration = Some(Duration::from_secs_f64(ms.value(cx).max(0.0) / 1000.0));
+    }
+    if let Some(chars) = js_options.get_opt::<JsString, _, _>(cx, "unquotedKeyChars")? {
+        options.unquoted_key_chars = chars.value(cx);
+    }
+    if let Some(policy) = js_options.get_opt::<JsString, _, _>(cx, "nonFiniteNumbers")? {
+        let policy = policy.value(cx);
+        options.non_finite_numbers = match policy.parse() {
+            Ok(policy) => policy,
+            Err(message) => return cx.throw_type_error(message),
+        };
+    }
+
+    Ok(FuzzyJsonParser::with_options(options))
+}
+
+/// Turns byte offsets into `text` into the UTF-16 offsets JS strings are indexed by, counting
+/// on from the last offset asked for when they come in order.
+struct Utf16Offsets<'t> {
+    text: &'t str,
+    byte: usize,
+    unit: usize,
+}
+
+impl<'t> Utf16Offsets<'t> {
+    fn new(text: &'t str) -> Self {
+        Self {
+            text,
+            byte: 0,
+            unit: 0,
         }
     }
+
+    fn of(&mut self, byte: usize) -> usize {
+        if byte < self.byte {
+            *self = Self::new(self.text);
+        }
+        let byte = byte.min(self.text.len());
+        self.unit += self.text[self.byte..byte].encode_utf16().count();
+        self.byte = byte;
+        self.unit
+    }
 }
 
-fn hello(mut cx: FunctionContext) -> JsResult<JsValue> {
-    let js_string = cx.argument::<JsString>(0)?;
-    let rust_string = js_string.value(&mut cx);
-    let parser = FuzzyJsonParser::new();
-    let result: serde_json::Value = parser.parse(&rust_string).unwrap();
+/// Throws a JS `Error` for `error`, with `kind` set to the variant name and `pos` to the
+/// offset into `text` where there is one.
+fn throw_error<'a, T>(
+    cx: &mut FunctionContext<'a>,
+    text: &str,
+    error: FuzzyJsonError,
+) -> NeonResult<T> {
+    let js_error = cx.error(error.to_string())?;
+    let kind = cx.string(error.kind());
+    js_error.set(cx, "kind", kind)?;
+    if let Some(location) = error.location() {
+        let pos = cx.number(Utf16Offsets::new(text).of(location.offset) as f64);
+        js_error.set(cx, "pos", pos)?;
+        let line = cx.number(location.line as f64);
+        js_error.set(cx, "line", line)?;
+        let column = cx.number(location.column as f64);
+        js_error.set(cx, "column", column)?;
+        let path = cx.string(&location.path);
+        js_error.set(cx, "path", path)?;
+    } else if let FuzzyJsonError::JsonError(e) = &error {
+        let line = cx.number(e.line() as f64);
+        js_error.set(cx, "line", line)?;
+        let column = cx.number(e.column() as f64);
+        js_error.set(cx, "column", column)?;
+    }
+    cx.throw(js_error)
+}
 
-    // Ok(cx.string("hello from rust"))
-    // Convert serde_json::Value to JsValue, then return
-    Ok(to_js_value(&mut cx, &result))
+/// `parse(text, options?)`: repairs `text` and returns the value.
+fn parse(mut cx: FunctionContext) -> JsResult<JsValue> {
+    let text = cx.argument::<JsString>(0)?.value(&mut cx);
+    let parser = parser_from_options(&mut cx, 1)?;
+
+    match parser.parse_value(&text) {
+        Ok(value) => to_js_value(&mut cx, &value),
+        Err(error) => throw_error(&mut cx, &text, error),
+    }
+}
+
+/// `extractAll(text, options?)`: every document embedded in `text`, as
+/// `{ value, start, end }` objects with offsets into `text` as `String.prototype.slice`
+/// takes them.
+fn extract_all(mut cx: FunctionContext) -> JsResult<JsArray> {
+    let text = cx.argument::<JsString>(0)?.value(&mut cx);
+    let parser = parser_from_options(&mut cx, 1)?;
+
+    let documents = parser.extract_all(&text);
+    let mut offsets = Utf16Offsets::new(&text);
+    let js_array = JsArray::new(&mut cx, documents.len());
+    for (i, document) in documents.iter().enumerate() {
+        let js_document = cx.empty_object();
+        let value = to_js_value(&mut cx, &document.value)?;
+        js_document.set(&mut cx, "value", value)?;
+        let start = cx.number(offsets.of(document.span.start) as f64);
+        js_document.set(&mut cx, "start", start)?;
+        let end = cx.number(offsets.of(document.span.end) as f64);
+        js_document.set(&mut cx, "end", end)?;
+        js_array.set(&mut cx, i as u32, js_document)?;
+    }
+    Ok(js_array)
 }
 
 #[neon::main]
 fn main(mut cx: ModuleContext) -> NeonResult<()> {
-    cx.export_function("hello", hello)?;
+    cx.export_function("parse", parse)?;
+    cx.export_function("extractAll", extract_all)?;
     Ok(())
 }
@@ -0,0 +1,79 @@
+const test = require("node:test");
+const assert = require("node:assert");
+const { parse, extractAll } = require("..");
+
+test("parses and repairs", () => {
+  assert.deepStrictEqual(parse("{'name': 'test', \"items\": [1, 2,"), {
+    name: "test",
+    items: [1, 2],
+  });
+});
+
+test("populates arrays", () => {
+  assert.deepStrictEqual(parse('[1, "two", [3], {"four": 4}]'), [1, "two", [3], { four: 4 }]);
+});
+
+test("keeps big integers exact", () => {
+  const value = parse('{"small": 42, "big": 9007199254740993, "negative": -9007199254740993, "float": 1.5}');
+  assert.strictEqual(value.small, 42);
+  assert.strictEqual(value.big, 9007199254740993n);
+  assert.strictEqual(value.negative, -9007199254740993n);
+  assert.strictEqual(value.float, 1.5);
+});
+
+test("maps options", () => {
+  assert.throws(() => parse('{"a": 1, // note\n}', { allowComments: false }));
+  assert.deepStrictEqual(parse('{"a": 1, // note\n}', { allowComments: true }), { a: 1 });
+});
+
+test("throws errors with kind and position", () => {
+  assert.throws(
+    () => parse('{"name": "test", "value":', { strictMode: true }),
+    (error) => {
+      assert.ok(error instanceof Error);
+      assert.strictEqual(error.kind, "StrictModeViolation");
+      assert.strictEqual(typeof error.pos, "number");
+      assert.strictEqual(error.line, 1);
+      assert.strictEqual(error.path, "/value");
+      return true;
+    },
+  );
+});
+
+test("enforces limits", () => {
+  assert.throws(
+    () => parse("[[1]]", { maxDepth: 1 }),
+    (error) => error.kind === "NestingTooDeep" && error.path === "/0",
+  );
+  assert.throws(
+    () => parse("{'a': 1}", { maxInputBytes: 4 }),
+    (error) => error.kind === "InputTooLarge" && error.pos === undefined,
+  );
+});
+
+test("extracts every document", () => {
+  const text = 'First ```json\n{"a": 1}\n``` then [2, 3';
+  const documents = extractAll(text);
+  assert.deepStrictEqual(
+    documents.map((document) => document.value),
+    [{ a: 1 }, [2, 3]],
+  );
+  assert.strictEqual(text.slice(documents[0].start, documents[0].end), '{"a": 1}');
+});
+
+test("reports offsets into the JS string", () => {
+  const text = 'Café 😀: {"a": 1} and ```json\n["é", 2]\n```';
+  const documents = extractAll(text);
+  assert.deepStrictEqual(
+    documents.map((document) => text.slice(document.start, document.end)),
+    ['{"a": 1}', '["é", 2]'],
+  );
+  assert.throws(
+    () => parse('{"é": "😀", "value":', { strictMode: true }),
+    (error) => error.pos === '{"é": "😀", "value":'.length,
+  );
+});
+
+test("keeps key order", () => {
+  assert.deepStrictEqual(Object.keys(parse("{'b': 1, 'a': 2, 'c': 3}")), ["b", "a", "c"]);
+});
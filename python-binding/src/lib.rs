@@ -0,0 +1,195 @@
+use chill_json::{FuzzyJsonError, FuzzyJsonParser, ParserOptions};
+use pyo3::create_exception;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyByteArray, PyBytes, PyDict, PyList};
+use serde_json::Value;
+use std::time::Duration;
+
+create_exception!(
+    chill_json,
+    JSONRepairError,
+    PyValueError,
+    "Raised when the input can't be repaired into JSON. `kind` names the `FuzzyJsonError` variant; `pos`, `lineno`, `colno` and `path` locate the problem where known (else `None`)."
+);
+
+fn to_py_object<'py>(py: Python<'py>, value: &Value) -> PyResult<Bound<'py, PyAny>> {
+    Ok(match value {
+        Value::Null => py.None().into_bound(py),
+        Value::Bool(b) => b.into_pyobject(py)?.to_owned().into_any(),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                i.into_pyobject(py)?.into_any()
+            } else if let Some(u) = n.as_u64() {
+                u.into_pyobject(py)?.into_any()
+            } else if !n.to_string().contains(['.', 'e', 'E']) {
+                // an integer beyond 64 bits, which Python's `int` holds exactly
+                py.import("builtins")?
+                    .getattr("int")?
+                    .call1((n.to_string(),))?
+            } else {
+                n.as_f64().unwrap_or(f64::NAN).into_pyobject(py)?.into_any()
+            }
+        }
+        Value::String(s) => s.into_pyobject(py)?.into_any(),
+        Value::Array(arr) => {
+            let list = PyList::empty(py);
+            for elem in arr {
+                list.append(to_py_object(py, elem)?)?;
+            }
+            list.into_any()
+        }
+        Value::Object(map) => {
+            let dict = PyDict::new(py);
+            for (k, v) in map {
+                dict.set_item(k, to_py_object(py, v)?)?;
+            }
+            dict.into_any()
+        }
+    })
+}
+
+/// Builds a parser from `loads`-style keyword arguments named after `ParserOptions` fields.
+fn parser_from_options(
+    function: &str,
+    options: Option<&Bound<'_, PyDict>>,
+) -> PyResult<FuzzyJsonParser> {
+    let mut parser_options = ParserOptions::default();
+    for (key, value) in options.into_iter().flatten() {
+        let key: String = key.extract()?;
+        match key.as_str() {
+            "auto_repair" => parser_options.auto_repair = value.extract()?,
+            "allow_trailing_commas" => parser_options.allow_trailing_commas = value.extract()?,
+            "allow_comments" => parser_options.allow_comments = value.extract()?,
+            "allow_single_quotes" => parser_options.allow_single_quotes = value.extract()?,
+            "allow_unquoted_keys" => parser_options.allow_unquoted_keys = value.extract()?,
+            "unquoted_key_chars" => parser_options.unquoted_key_chars = value.extract()?,
+            "allow_bareword_values" => parser_options.allow_bareword_values = value.extract()?,
+            "max_repair_attempts" => parser_options.max_repair_attempts = value.extract()?,
+            "strict_mode" => parser_options.strict_mode = value.extract()?,
+            "aggressive_truncation_repair" => {
+                parser_options.aggressive_truncation_repair = value.extract()?
+            }
+            "allow_backtick_strings" => parser_options.allow_backtick_strings = value.extract()?,
+            "allow_python_literals" => parser_options.allow_python_literals = value.extract()?,
+            "max_input_bytes" => parser_options.max_input_bytes = value.extract()?,
+            "max_depth" => parser_options.max_depth = value.extract()?,
+            "max_string_length" => parser_options.max_string_length = value.extract()?,
+            "max_entries" => parser_options.max_entries = value.extract()?,
+            "max_steps" => parser_options.max_steps = value.extract()?,
+            "max_duration" => {
+                let seconds: Option<f64> = value.extract()?;
+                parser_options.max_duration = seconds
+                    .map(Duration::try_from_secs_f64)
+                    .transpose()
+                    .map_err(|e| PyValueError::new_err(e.to_string()))?;
+            }
+            "non_finite_numbers" => {
+                let policy: String = value.extract()?;
+                parser_options.non_finite_numbers =
+                    policy.parse().map_err(PyValueError::new_err)?;
+            }
+            "schema" if value.is_none() => parser_options.schema = None,
+            "schema" => {
+                let schema: String = value
+                    .py()
+                    .import("json")?
+                    .call_method1("dumps", (value,))?
+                    .extract()?;
+                parser_options.schema = Some(
+                    serde_json::from_str(&schema)
+                        .map_err(|e| PyValueError::new_err(e.to_string()))?,
+                );
+            }
+            _ => {
+                return Err(PyTypeError::new_err(format!(
+                    "{function}() got an unexpected keyword argument '{key}'"
+                )));
+            }
+        }
+    }
+    Ok(FuzzyJsonParser::with_options(parser_options))
+}
+
+/// Converts `error` to a `JSONRepairError` whose `pos` is, like `json.JSONDecodeError.pos`,
+/// a character index into `text` rather than the byte offset the parser works with.
+fn to_py_err(py: Python<'_>, text: &str, error: FuzzyJsonError) -> PyErr {
+    let location = error.location();
+    let err = JSONRepairError::new_err(error.to_string());
+    let value = err.value(py);
+    // setting attributes on a fresh exception instance only fails when out of memory
+    let _ = value.setattr("kind", error.kind());
+    let _ = value.setattr("msg", error.message());
+    let _ = value.setattr(
+        "pos",
+        location.map(|location| text[..location.offset.min(text.len())].chars().count()),
+    );
+    let _ = value.setattr("lineno", location.map(|location| location.line));
+    let _ = value.setattr("colno", location.map(|location| location.column));
+    let _ = value.setattr("path", location.map(|location| location.path.as_str()));
+    err
+}
+
+/// The text of a `loads` argument, decoding `bytes` and `bytearray` the way `json.loads`
+/// does.
+fn document(text: &Bound<'_, PyAny>) -> PyResult<String> {
+    if text.is_instance_of::<PyBytes>() || text.is_instance_of::<PyByteArray>() {
+        let encoding = text
+            .py()
+            .import("json")?
+            .call_method1("detect_encoding", (text,))?;
+        return text.call_method1("decode", (encoding,))?.extract();
+    }
+    if let Ok(text) = text.extract() {
+        return Ok(text);
+    }
+    Err(PyTypeError::new_err(format!(
+        "the JSON object must be str, bytes or bytearray, not {}",
+        text.get_type().name()?
+    )))
+}
+
+fn repair(
+    py: Python<'_>,
+    function: &str,
+    text: &Bound<'_, PyAny>,
+    options: Option<&Bound<'_, PyDict>>,
+) -> PyResult<Value> {
+    let parser = parser_from_options(function, options)?;
+    let text = document(text)?;
+    py.detach(|| parser.parse_value(&text))
+        .map_err(|error| to_py_err(py, &text, error))
+}
+
+/// `loads(text, **options)`: like `json.loads`, but repairs `text` first.
+#[pyfunction]
+#[pyo3(signature = (text, **options))]
+fn loads<'py>(
+    py: Python<'py>,
+    text: &Bound<'py, PyAny>,
+    options: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let value = repair(py, "loads", text, options)?;
+    to_py_object(py, &value)
+}
+
+/// `repair_json(text, **options)`: the repaired document as a JSON string.
+#[pyfunction]
+#[pyo3(signature = (text, **options))]
+fn repair_json(
+    py: Python<'_>,
+    text: &Bound<'_, PyAny>,
+    options: Option<&Bound<'_, PyDict>>,
+) -> PyResult<String> {
+    let value = repair(py, "repair_json", text, options)?;
+    Ok(value.to_string())
+}
+
+#[pymodule]
+#[pyo3(name = "chill_json")]
+fn chill_json_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(loads, m)?)?;
+    m.add_function(wrap_pyfunction!(repair_json, m)?)?;
+    m.add("JSONRepairError", m.py().get_type::<JSONRepairError>())?;
+    Ok(())
+}
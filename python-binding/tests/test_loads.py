@@ -0,0 +1,93 @@
+import json
+
+import pytest
+
+import chill_json
+
+
+def test_loads_valid_json():
+    assert chill_json.loads('{"name": "test", "items": [1, 2.5, null, true]}') == {
+        "name": "test",
+        "items": [1, 2.5, None, True],
+    }
+
+
+def test_loads_repairs():
+    text = "Here you go:\n```json\n{'name': 'São Paulo', \"items\": [1, 2,"
+    assert chill_json.loads(text) == {"name": "São Paulo", "items": [1, 2]}
+
+
+def test_big_integers_stay_exact():
+    assert chill_json.loads('[18446744073709551615, -9223372036854775808]') == [
+        18446744073709551615,
+        -9223372036854775808,
+    ]
+    assert chill_json.loads("[123456789012345678901234567890, 1.5e3]") == [
+        123456789012345678901234567890,
+        1500.0,
+    ]
+    assert chill_json.repair_json("[123456789012345678901234567890,") == (
+        "[123456789012345678901234567890]"
+    )
+
+
+def test_keys_keep_their_order():
+    assert list(chill_json.loads("{'b': 1, 'a': 2, 'c': 3}")) == ["b", "a", "c"]
+    assert chill_json.repair_json('{"b": 1, "a": 2}') == '{"b":1,"a":2}'
+
+
+def test_bytes_input():
+    assert chill_json.loads(b"{'city': 'S\xc3\xa3o Paulo'") == {"city": "São Paulo"}
+    assert chill_json.loads(bytearray(b"[1, 2,")) == [1, 2]
+    assert chill_json.loads('[1, "é"]'.encode("utf-16")) == [1, "é"]
+    with pytest.raises(TypeError, match="not int"):
+        chill_json.loads(1)
+
+
+def test_options():
+    text = '{"a": 1, // note\n}'
+    assert chill_json.loads(text, allow_comments=True) == {"a": 1}
+    with pytest.raises(chill_json.JSONRepairError):
+        chill_json.loads(text, allow_comments=False)
+
+    with pytest.raises(TypeError, match="unexpected keyword argument 'bogus'"):
+        chill_json.loads("{}", bogus=True)
+
+
+def test_schema_option():
+    schema = {"properties": {"unit": {"enum": ["celsius", "fahrenheit"]}}}
+    assert chill_json.loads('{"unit": "Celsius"}', schema=schema) == {"unit": "celsius"}
+
+
+def test_error_carries_kind_and_pos():
+    with pytest.raises(chill_json.JSONRepairError) as info:
+        chill_json.loads('{"città": "Roma", "value":', strict_mode=True)
+    assert isinstance(info.value, ValueError)
+    assert info.value.kind == "StrictModeViolation"
+    assert info.value.pos == len('{"città": "Roma", "value":')
+    assert (info.value.lineno, info.value.colno) == (1, info.value.pos + 1)
+    assert info.value.path == "/value"
+
+    with pytest.raises(chill_json.JSONRepairError) as info:
+        chill_json.loads("{'a': 1", auto_repair=False)
+    assert info.value.kind == "RepairFailed"
+    assert info.value.pos is None
+
+
+def test_limits():
+    with pytest.raises(chill_json.JSONRepairError) as info:
+        chill_json.loads("[[[1]]]", max_depth=2)
+    assert info.value.kind == "NestingTooDeep"
+    assert info.value.path == "/0/0"
+
+    with pytest.raises(chill_json.JSONRepairError) as info:
+        chill_json.loads("{'a': 1}", max_input_bytes=4)
+    assert info.value.kind == "InputTooLarge"
+    assert info.value.pos is None
+
+    assert chill_json.loads("{'a': 1}", max_duration=1.5, max_steps=None) == {"a": 1}
+
+
+def test_repair_json():
+    repaired = chill_json.repair_json("{'name': 'test', \"items\": [1, 2,")
+    assert json.loads(repaired) == {"name": "test", "items": [1, 2]}
@@ -0,0 +1,695 @@
+//! `serde::Deserializer` that repairs the input as the value being deserialized asks for
+//! more of it, rather than repairing all of it into a `serde_json::Value` first.
+
+use crate::path::PathTracker;
+use crate::{ErrorLocation, FuzzyJsonError, FuzzyJsonParser, ParseState};
+use serde::Deserializer as _;
+use serde::de::{self, DeserializeSeed, IgnoredAny, Unexpected, Visitor};
+use serde_json::de::StrRead;
+
+/// How deep values may nest before deserializing them would risk the stack, the same as
+/// `serde_json`'s limit.
+const RECURSION_LIMIT: usize = 128;
+
+/// Deserializes a `T` from messy input with the default options, like `serde_json::from_str`
+/// does from JSON.
+///
+/// ```
+/// #[derive(serde::Deserialize)]
+/// struct Tool<'a> {
+///     name: &'a str,
+///     retries: u32,
+/// }
+///
+/// let tool: Tool = chill_json::from_str("{name: 'search', \"retries\": 3,").unwrap();
+/// assert_eq!((tool.name, tool.retries), ("search", 3));
+/// ```
+pub fn from_str<'de, T>(input: &'de str) -> Result<T, FuzzyJsonError>
+where
+    T: de::Deserialize<'de>,
+{
+    FuzzyJsonParser::new().parse(input)
+}
+
+/// Deserializes straight from input that may need repair. Input that already is JSON is
+/// handed to `serde_json`; anything else is run through the parser's handlers and repair
+/// strategies a token at a time.
+///
+/// Strings the repair copied over unchanged are borrowed from the input, so `&str` fields
+/// work for them. Call `end` afterwards to check the whole input was used.
+pub struct Deserializer<'p, 'de> {
+    inner: Inner<'p, 'de>,
+}
+
+enum Inner<'p, 'de> {
+    Valid {
+        json: serde_json::Deserializer<StrRead<'de>>,
+        input: &'de str,
+    },
+    Repair(Box<Repair<'p, 'de>>),
+}
+
+impl<'p, 'de> Deserializer<'p, 'de> {
+    pub fn new(parser: &'p FuzzyJsonParser, input: &'de str) -> Result<Self, FuzzyJsonError> {
+        parser.check_input_size(input.len())?;
+        match serde_json::from_str::<IgnoredAny>(input) {
+            Ok(_) => {
+                parser.check_valid(input)?;
+                Ok(Self::valid(input))
+            }
+            Err(e) => Self::repairing(parser, input, &e),
+        }
+    }
+
+    /// For `input` that `serde_json` already failed to deserialize with `e`. Fails with `e`
+    /// itself if the input was JSON and just didn't fit the type.
+    pub(crate) fn after_failure(
+        parser: &'p FuzzyJsonParser,
+        input: &'de str,
+        e: serde_json::Error,
+    ) -> Result<Self, FuzzyJsonError> {
+        if !e.is_data() {
+            return Self::repairing(parser, input, &e);
+        }
+        match serde_json::from_str::<IgnoredAny>(input) {
+            Ok(_) => Err(json_mismatch(input, e)),
+            Err(syntax) => Self::repairing(parser, input, &syntax),
+        }
+    }
+
+    fn valid(input: &'de str) -> Self {
+        Self {
+            inner: Inner::Valid {
+                json: serde_json::Deserializer::from_str(input),
+                input,
+            },
+        }
+    }
+
+    fn repairing(
+        parser: &'p FuzzyJsonParser,
+        input: &'de str,
+        e: &serde_json::Error,
+    ) -> Result<Self, FuzzyJsonError> {
+        if !parser.options.auto_repair {
+            return Err(FuzzyJsonError::RepairFailed(
+                "Auto-repair disabled".to_string(),
+            ));
+        }
+        let error = e.to_string();
+        let state = parser.start_repair(input, &error)?;
+        Ok(Self {
+            inner: Inner::Repair(Box::new(Repair {
+                parser,
+                input,
+                state,
+                error,
+                done: false,
+                read: 0,
+                depth: 0,
+                steps: vec![(0, 0)],
+            })),
+        })
+    }
+
+    /// Checks that nothing but whitespace follows the value.
+    pub fn end(&mut self) -> Result<(), FuzzyJsonError> {
+        match &mut self.inner {
+            Inner::Valid { json, input } => json.end().map_err(|e| json_mismatch(input, e)),
+            Inner::Repair(repair) => repair.end(),
+        }
+    }
+}
+
+/// `serde_json`'s error for input that is valid JSON, which can only mean it didn't fit the
+/// type, located by the line and column `serde_json` reports.
+fn json_mismatch(input: &str, e: serde_json::Error) -> FuzzyJsonError {
+    let message = e.to_string();
+    let position = format!(" at line {} column {}", e.line(), e.column());
+    let message = message
+        .strip_suffix(&position)
+        .unwrap_or(&message)
+        .to_string();
+    let location = (e.line() > 0).then(|| {
+        let line_start: usize = input
+            .split_inclusive('\n')
+            .take(e.line() - 1)
+            .map(str::len)
+            .sum();
+        let mut offset = (line_start + e.column().saturating_sub(1)).min(input.len());
+        while !input.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let path = PathTracker::default().pointer(&input[..offset]);
+        ErrorLocation::new(input, offset, path)
+    });
+    FuzzyJsonError::DeserializationMismatch { message, location }
+}
+
+macro_rules! forward {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {$(
+        fn $method<V: Visitor<'de>>(
+            self,
+            $($arg: $ty,)*
+            visitor: V,
+        ) -> Result<V::Value, FuzzyJsonError> {
+            match &mut self.inner {
+                Inner::Valid { json, input } => json
+                    .$method($($arg,)* visitor)
+                    .map_err(|e| json_mismatch(input, e)),
+                Inner::Repair(repair) => repair.as_mut().$method($($arg,)* visitor),
+            }
+        }
+    )*};
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'_, 'de> {
+    type Error = FuzzyJsonError;
+
+    forward! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_i128();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_u128();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_str();
+        deserialize_string();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_option();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+        deserialize_ignored_any();
+    }
+}
+
+/// Reads JSON tokens off the output of a repair that is still running, stepping it only
+/// when the value being deserialized needs more than it has produced so far.
+struct Repair<'p, 'de> {
+    parser: &'p FuzzyJsonParser,
+    input: &'de str,
+    state: ParseState,
+    error: String,
+    /// The repair has nothing left to do, the output is complete.
+    done: bool,
+    /// Bytes of the output deserialized so far.
+    read: usize,
+    /// Arrays and objects being deserialized.
+    depth: usize,
+    /// Settled output length and input position before the steps that settled more of the
+    /// output, to trace output back to the input it came from.
+    steps: Vec<(usize, usize)>,
+}
+
+impl<'de> Repair<'_, 'de> {
+    /// Length of the output later steps can't take back any more.
+    fn settled(&self) -> usize {
+        if self.done {
+            return self.state.output.len();
+        }
+        self.state
+            .output
+            .trim_end_matches(|c: char| c.is_whitespace() || c == ',')
+            .len()
+    }
+
+    fn pump(&mut self) -> Result<(), FuzzyJsonError> {
+        let settled = self.settled();
+        match self.steps.last_mut() {
+            Some((last, position)) if *last == settled => *position = self.state.position,
+            _ => self.steps.push((settled, self.state.position)),
+        }
+        self.done = !self.parser.run_step(&mut self.state, &self.error)?;
+        Ok(())
+    }
+
+    /// Skips whitespace and returns the next byte of the output, repairing more of the input
+    /// until there is one. `None` once the output is over.
+    fn peek(&mut self) -> Result<Option<u8>, FuzzyJsonError> {
+        loop {
+            let settled = self.settled();
+            let output = self.state.output.as_bytes();
+            while self.read < settled && output[self.read].is_ascii_whitespace() {
+                self.read += 1;
+            }
+            if self.read < settled {
+                return Ok(Some(output[self.read]));
+            }
+            if self.done {
+                return Ok(None);
+            }
+            self.pump()?;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), FuzzyJsonError> {
+        match self.peek()? {
+            Some(next) if next == byte => {
+                self.read += 1;
+                Ok(())
+            }
+            _ => Err(self.syntax_error(&format!("expected `{}`", byte as char))),
+        }
+    }
+
+    /// Moves into the array or object that starts at `read`.
+    fn enter(&mut self) -> Result<(), FuzzyJsonError> {
+        self.depth += 1;
+        if self.depth > RECURSION_LIMIT {
+            return Err(self.syntax_error("recursion limit exceeded"));
+        }
+        self.read += 1;
+        Ok(())
+    }
+
+    /// Moves past the `close` of the array or object `enter` moved into.
+    fn leave(&mut self, close: u8) -> Result<(), FuzzyJsonError> {
+        self.depth -= 1;
+        self.expect(close)
+    }
+
+    fn end(&mut self) -> Result<(), FuzzyJsonError> {
+        match self.peek()? {
+            None => Ok(()),
+            Some(_) => Err(self.syntax_error("trailing characters")),
+        }
+    }
+
+    /// End of the string, number or literal starting at `read`. Stepping on while it may
+    /// still grow.
+    fn token_end(&mut self) -> Result<usize, FuzzyJsonError> {
+        loop {
+            let settled = self.settled();
+            let output = &self.state.output.as_bytes()[..settled];
+            let is_string = output[self.read] == b'"';
+            let end = if is_string {
+                let mut i = self.read + 1;
+                loop {
+                    match output.get(i) {
+                        Some(b'\\') => i += 2,
+                        Some(b'"') => break Some(i + 1),
+                        Some(_) => i += 1,
+                        None => break None,
+                    }
+                }
+            } else {
+                output[self.read..]
+                    .iter()
+                    .position(|b| b.is_ascii_whitespace() || b",:]}".contains(b))
+                    .map(|len| self.read + len)
+            };
+            match end {
+                Some(end) => return Ok(end),
+                None if self.done && is_string => {
+                    return Err(self.syntax_error("EOF while parsing a string"));
+                }
+                None if self.done => return Ok(settled),
+                None => self.pump()?,
+            }
+        }
+    }
+
+    fn scalar<V: Visitor<'de>>(&mut self, visitor: V) -> Result<V::Value, FuzzyJsonError> {
+        let start = self.read;
+        let end = self.token_end()?;
+        if end == start {
+            return Err(self.syntax_error("expected value"));
+        }
+        self.read = end;
+        let token = &self.state.output[start..end];
+        match token {
+            "true" => visitor.visit_bool(true),
+            "false" => visitor.visit_bool(false),
+            "null" => visitor.visit_unit(),
+            _ if token.starts_with('"') => {
+                let content = &token[1..token.len() - 1];
+                if content.contains('\\') {
+                    let decoded: String = serde_json::from_str(token)?;
+                    visitor.visit_string(decoded)
+                } else if let Some(borrowed) = self.borrowed(start, content) {
+                    visitor.visit_borrowed_str(borrowed)
+                } else {
+                    visitor.visit_str(content)
+                }
+            }
+            _ => {
+                let number: serde_json::Number = serde_json::from_str(token)?;
+                if let Some(n) = number.as_u64() {
+                    visitor.visit_u64(n)
+                } else if let Some(n) = number.as_i64() {
+                    visitor.visit_i64(n)
+                } else {
+                    visitor.visit_f64(number.as_f64().unwrap_or(f64::NAN))
+                }
+            }
+        }
+    }
+
+    /// `content`, a string from the output starting at `at`, as it appears in the input the
+    /// step that wrote it consumed. `None` when the repair changed it, e.g. re-quoting it.
+    fn borrowed(&self, at: usize, content: &str) -> Option<&'de str> {
+        let step = self.step_at(at);
+        let from = self.steps[step].1;
+        let to = self
+            .steps
+            .get(step + 1)
+            .map_or(self.state.position, |&(_, position)| position);
+        let consumed = self.input.get(from..to)?;
+        let offset = consumed.find(content)?;
+        Some(&consumed[offset..offset + content.len()])
+    }
+
+    /// Index of the step that wrote output byte `at`.
+    fn step_at(&self, at: usize) -> usize {
+        self.steps
+            .partition_point(|&(settled, _)| settled <= at)
+            .saturating_sub(1)
+    }
+
+    /// Gives a mismatch the location of the input the output from `at` on came from.
+    fn located(&self, e: FuzzyJsonError, at: usize) -> FuzzyJsonError {
+        match e {
+            FuzzyJsonError::DeserializationMismatch {
+                message,
+                location: None,
+            } => {
+                let input = &self.state.input;
+                let from = self.steps[self.step_at(at)].1.min(input.len());
+                let offset = input.len() - input[from..].trim_start().len();
+                let path = PathTracker::default().pointer(&self.state.output[..at]);
+                FuzzyJsonError::DeserializationMismatch {
+                    message,
+                    location: Some(ErrorLocation::new(input, offset, path)),
+                }
+            }
+            e => e,
+        }
+    }
+
+    /// The repaired output isn't JSON after all.
+    fn syntax_error(&self, message: &str) -> FuzzyJsonError {
+        FuzzyJsonError::JsonError(de::Error::custom(message))
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Repair<'_, 'de> {
+    type Error = FuzzyJsonError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FuzzyJsonError> {
+        let Some(next) = self.peek()? else {
+            return Err(self.syntax_error("EOF while parsing a value"));
+        };
+        let at = self.read;
+        let value = match next {
+            b'{' => {
+                self.enter()?;
+                visitor
+                    .visit_map(Compound::new(self))
+                    .and_then(|value| self.leave(b'}').map(|_| value))
+            }
+            b'[' => {
+                self.enter()?;
+                visitor
+                    .visit_seq(Compound::new(self))
+                    .and_then(|value| self.leave(b']').map(|_| value))
+            }
+            _ => self.scalar(visitor),
+        };
+        value.map_err(|e| self.located(e, at))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FuzzyJsonError> {
+        if self.peek()? == Some(b'n') {
+            let end = self.token_end()?;
+            if &self.state.output[self.read..end] == "null" {
+                self.read = end;
+                return visitor.visit_none();
+            }
+        }
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, FuzzyJsonError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, FuzzyJsonError> {
+        let at = self.read;
+        let value = match self.peek()? {
+            Some(b'"') => visitor.visit_enum(Variant {
+                de: &mut *self,
+                unit: true,
+            }),
+            Some(b'{') => {
+                self.enter()?;
+                visitor
+                    .visit_enum(Variant {
+                        de: &mut *self,
+                        unit: false,
+                    })
+                    .and_then(|value| self.leave(b'}').map(|_| value))
+            }
+            _ => return self.deserialize_any(visitor),
+        };
+        value.map_err(|e| self.located(e, at))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// Entries of an array or object.
+struct Compound<'a, 'p, 'de> {
+    de: &'a mut Repair<'p, 'de>,
+    first: bool,
+}
+
+impl<'a, 'p, 'de> Compound<'a, 'p, 'de> {
+    fn new(de: &'a mut Repair<'p, 'de>) -> Self {
+        Self { de, first: true }
+    }
+
+    /// Moves past the comma before the next entry. False if `close` comes instead.
+    fn has_next(&mut self, close: u8) -> Result<bool, FuzzyJsonError> {
+        let first = std::mem::replace(&mut self.first, false);
+        match self.de.peek()? {
+            Some(next) if next == close => Ok(false),
+            Some(b',') if !first => {
+                self.de.read += 1;
+                Ok(true)
+            }
+            Some(_) if first => Ok(true),
+            _ => Err(self
+                .de
+                .syntax_error(&format!("expected `,` or `{}`", close as char))),
+        }
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for Compound<'_, '_, 'de> {
+    type Error = FuzzyJsonError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, FuzzyJsonError> {
+        if !self.has_next(b']')? {
+            return Ok(None);
+        }
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+}
+
+impl<'de> de::MapAccess<'de> for Compound<'_, '_, 'de> {
+    type Error = FuzzyJsonError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, FuzzyJsonError> {
+        if !self.has_next(b'}')? {
+            return Ok(None);
+        }
+        if self.de.peek()? != Some(b'"') {
+            return Err(self.de.syntax_error("key must be a string"));
+        }
+        seed.deserialize(MapKey { de: &mut *self.de }).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, FuzzyJsonError> {
+        self.de.expect(b':')?;
+        seed.deserialize(&mut *self.de)
+    }
+}
+
+/// Object keys are always strings, but may stand for numbers as in `HashMap<u32, _>`.
+struct MapKey<'a, 'p, 'de> {
+    de: &'a mut Repair<'p, 'de>,
+}
+
+macro_rules! numeric_key {
+    ($($method:ident => $visit:ident,)*) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FuzzyJsonError> {
+            let at = self.de.read;
+            let key: String = de::Deserialize::deserialize(&mut *self.de)?;
+            match key.parse() {
+                Ok(n) => visitor.$visit(n),
+                Err(_) => Err(de::Error::invalid_value(Unexpected::Str(&key), &visitor)),
+            }
+            .map_err(|e| self.de.located(e, at))
+        }
+    )*};
+}
+
+impl<'de> de::Deserializer<'de> for MapKey<'_, '_, 'de> {
+    type Error = FuzzyJsonError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FuzzyJsonError> {
+        self.de.deserialize_any(visitor)
+    }
+
+    numeric_key! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FuzzyJsonError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, FuzzyJsonError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, FuzzyJsonError> {
+        self.de.deserialize_enum(name, variants, visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i128 u128 char str string bytes byte_buf unit unit_struct seq tuple tuple_struct
+        map struct identifier ignored_any
+    }
+}
+
+/// An enum as a bare variant name, or as an object with the variant name as its only key.
+struct Variant<'a, 'p, 'de> {
+    de: &'a mut Repair<'p, 'de>,
+    unit: bool,
+}
+
+impl<'de> de::EnumAccess<'de> for Variant<'_, '_, 'de> {
+    type Error = FuzzyJsonError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self), FuzzyJsonError> {
+        let variant = seed.deserialize(MapKey { de: &mut *self.de })?;
+        if !self.unit {
+            self.de.expect(b':')?;
+        }
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Variant<'_, '_, 'de> {
+    type Error = FuzzyJsonError;
+
+    fn unit_variant(self) -> Result<(), FuzzyJsonError> {
+        match self.unit {
+            true => Ok(()),
+            false => de::Deserialize::deserialize(self.de),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, FuzzyJsonError> {
+        match self.unit {
+            true => Err(de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"newtype variant",
+            )),
+            false => seed.deserialize(self.de),
+        }
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, FuzzyJsonError> {
+        match self.unit {
+            true => Err(de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"tuple variant",
+            )),
+            false => self.de.deserialize_seq(visitor),
+        }
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, FuzzyJsonError> {
+        match self.unit {
+            true => Err(de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"struct variant",
+            )),
+            false => self.de.deserialize_map(visitor),
+        }
+    }
+}
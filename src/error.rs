@@ -0,0 +1,337 @@
+//! Errors, and where in the input they were found.
+
+use std::fmt;
+use std::time::Duration;
+
+/// Longest stretch of the error line shown on either side of the caret.
+const SNIPPET_CONTEXT: usize = 40;
+
+#[derive(Debug)]
+pub enum FuzzyJsonError {
+    /// A character neither a handler nor a repair strategy could make sense of.
+    UnexpectedCharacter {
+        found: char,
+        location: ErrorLocation,
+    },
+    /// A `}` where an array is open or a `]` where an object is.
+    UnbalancedClose {
+        found: char,
+        location: ErrorLocation,
+    },
+    /// The input stopped inside a string, array or object that truncation repair didn't
+    /// close, see `ParserOptions::aggressive_truncation_repair`.
+    UnexpectedEnd { location: ErrorLocation },
+    /// A second document starts after the first one, as in `{"a": 1} {"b": 2}` or
+    /// `{"a": 1} Or: {"b": 2}`, and there is no telling which one was meant.
+    AmbiguousDocuments { location: ErrorLocation },
+    /// A `\` escape that no repair strategy took care of.
+    InvalidEscape { location: ErrorLocation },
+    /// A number with more than one decimal point, like the version-like `1.5.3`, which may
+    /// as well be a typo for `1.53`.
+    InvalidNumber { location: ErrorLocation },
+    /// The input needed more than `ParserOptions::max_repair_attempts` repairs.
+    ExceededRepairAttempts {
+        attempts: usize,
+        location: ErrorLocation,
+    },
+    /// The input is longer than `ParserOptions::max_input_bytes`.
+    InputTooLarge { size: usize, limit: usize },
+    /// Objects and arrays nest deeper than `ParserOptions::max_depth`.
+    NestingTooDeep {
+        limit: usize,
+        location: ErrorLocation,
+    },
+    /// A string is longer than `ParserOptions::max_string_length`.
+    StringTooLong {
+        limit: usize,
+        location: ErrorLocation,
+    },
+    /// An object or array has more than `ParserOptions::max_entries` entries.
+    TooManyEntries {
+        limit: usize,
+        location: ErrorLocation,
+    },
+    /// Repair took more than `ParserOptions::max_steps` steps.
+    ExceededStepBudget {
+        steps: usize,
+        location: ErrorLocation,
+    },
+    /// Repair took longer than `ParserOptions::max_duration`.
+    ExceededTimeBudget {
+        elapsed: Duration,
+        location: ErrorLocation,
+    },
+    /// Strict mode refused a repair that would drop input or invent values.
+    StrictModeViolation {
+        strategy: &'static str,
+        location: ErrorLocation,
+    },
+    /// The repaired value doesn't fit the type or `Shape` asked for. The location is only
+    /// known when the mismatch could be traced back to the input.
+    DeserializationMismatch {
+        message: String,
+        location: Option<ErrorLocation>,
+    },
+    /// Repair wasn't attempted, e.g. because `ParserOptions::auto_repair` is off.
+    RepairFailed(String),
+    /// The repaired output still isn't JSON.
+    JsonError(serde_json::Error),
+}
+
+impl FuzzyJsonError {
+    /// For the repaired value failing to deserialize into the type asked for.
+    pub(crate) fn mismatch(e: serde_json::Error) -> Self {
+        Self::DeserializationMismatch {
+            message: e.to_string(),
+            location: None,
+        }
+    }
+
+    /// Name of the variant, as bindings report it.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::UnexpectedCharacter { .. } => "UnexpectedCharacter",
+            Self::UnbalancedClose { .. } => "UnbalancedClose",
+            Self::UnexpectedEnd { .. } => "UnexpectedEnd",
+            Self::AmbiguousDocuments { .. } => "AmbiguousDocuments",
+            Self::InvalidEscape { .. } => "InvalidEscape",
+            Self::InvalidNumber { .. } => "InvalidNumber",
+            Self::ExceededRepairAttempts { .. } => "ExceededRepairAttempts",
+            Self::InputTooLarge { .. } => "InputTooLarge",
+            Self::NestingTooDeep { .. } => "NestingTooDeep",
+            Self::StringTooLong { .. } => "StringTooLong",
+            Self::TooManyEntries { .. } => "TooManyEntries",
+            Self::ExceededStepBudget { .. } => "ExceededStepBudget",
+            Self::ExceededTimeBudget { .. } => "ExceededTimeBudget",
+            Self::StrictModeViolation { .. } => "StrictModeViolation",
+            Self::DeserializationMismatch { .. } => "DeserializationMismatch",
+            Self::RepairFailed(_) => "RepairFailed",
+            Self::JsonError(_) => "JsonError",
+        }
+    }
+
+    pub fn location(&self) -> Option<&ErrorLocation> {
+        match self {
+            Self::UnexpectedCharacter { location, .. }
+            | Self::UnbalancedClose { location, .. }
+            | Self::UnexpectedEnd { location }
+            | Self::AmbiguousDocuments { location }
+            | Self::InvalidEscape { location }
+            | Self::InvalidNumber { location }
+            | Self::ExceededRepairAttempts { location, .. }
+            | Self::NestingTooDeep { location, .. }
+            | Self::StringTooLong { location, .. }
+            | Self::TooManyEntries { location, .. }
+            | Self::ExceededStepBudget { location, .. }
+            | Self::ExceededTimeBudget { location, .. }
+            | Self::StrictModeViolation { location, .. } => Some(location),
+            Self::DeserializationMismatch { location, .. } => location.as_ref(),
+            Self::InputTooLarge { .. } | Self::RepairFailed(_) | Self::JsonError(_) => None,
+        }
+    }
+
+    /// One-line description with the line and column but without the path and snippet
+    /// `Display` adds, e.g. `Unexpected character '@' at line 3, column 9`.
+    pub fn message(&self) -> String {
+        let headline = self.headline();
+        match self.location() {
+            Some(location) => format!(
+                "{headline} at line {}, column {}",
+                location.line, location.column
+            ),
+            None => headline,
+        }
+    }
+
+    /// What went wrong, without saying where. Repair strategies get to see this while the
+    /// location is still unresolved, see `ErrorLocation::pending`.
+    pub(crate) fn headline(&self) -> String {
+        match self {
+            Self::UnexpectedCharacter { found, .. } => format!("Unexpected character {found:?}"),
+            Self::UnbalancedClose { found, .. } => {
+                let open = if *found == '}' {
+                    "an array"
+                } else {
+                    "an object"
+                };
+                format!("Unbalanced {found:?} inside {open}")
+            }
+            Self::UnexpectedEnd { .. } => "Input ended in the middle of a value".to_string(),
+            Self::AmbiguousDocuments { .. } => "Another document follows the first one".to_string(),
+            Self::InvalidEscape { .. } => "Invalid escape sequence in string".to_string(),
+            Self::InvalidNumber { .. } => "Number with more than one decimal point".to_string(),
+            Self::ExceededRepairAttempts { attempts, .. } => {
+                format!("Gave up after {attempts} repair attempts")
+            }
+            Self::InputTooLarge { size, limit } => {
+                format!("Input of {size} bytes exceeds the limit of {limit}")
+            }
+            Self::NestingTooDeep { limit, .. } => {
+                format!("Nesting exceeds the depth limit of {limit}")
+            }
+            Self::StringTooLong { limit, .. } => {
+                format!("String exceeds the length limit of {limit} bytes")
+            }
+            Self::TooManyEntries { limit, .. } => {
+                format!("Object or array exceeds the limit of {limit} entries")
+            }
+            Self::ExceededStepBudget { steps, .. } => format!("Gave up after {steps} steps"),
+            Self::ExceededTimeBudget { elapsed, .. } => format!("Gave up after {elapsed:?}"),
+            Self::StrictModeViolation { strategy, .. } => {
+                format!("Strict mode refused the `{strategy}` repair")
+            }
+            Self::DeserializationMismatch { message, .. } => message.clone(),
+            Self::RepairFailed(message) => format!("Repair failed: {message}"),
+            Self::JsonError(e) => format!("JSON error: {e}"),
+        }
+    }
+
+    /// Works out the line, column and snippet of a location found while repairing `input`,
+    /// once the error is on its way to the caller.
+    pub(crate) fn resolve(mut self, input: &str) -> Self {
+        if let Some(location) = self.location_mut() {
+            location.resolve(input);
+        }
+        self
+    }
+
+    fn location_mut(&mut self) -> Option<&mut ErrorLocation> {
+        match self {
+            Self::UnexpectedCharacter { location, .. }
+            | Self::UnbalancedClose { location, .. }
+            | Self::UnexpectedEnd { location }
+            | Self::AmbiguousDocuments { location }
+            | Self::InvalidEscape { location }
+            | Self::InvalidNumber { location }
+            | Self::ExceededRepairAttempts { location, .. }
+            | Self::NestingTooDeep { location, .. }
+            | Self::StringTooLong { location, .. }
+            | Self::TooManyEntries { location, .. }
+            | Self::ExceededStepBudget { location, .. }
+            | Self::ExceededTimeBudget { location, .. }
+            | Self::StrictModeViolation { location, .. } => Some(location),
+            Self::DeserializationMismatch { location, .. } => location.as_mut(),
+            Self::InputTooLarge { .. } | Self::RepairFailed(_) | Self::JsonError(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for FuzzyJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message())?;
+        if let Some(location) = self.location() {
+            if !location.path.is_empty() {
+                write!(f, " ({})", location.path)?;
+            }
+            write!(f, "\n{}", location.snippet)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for FuzzyJsonError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::JsonError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl serde::de::Error for FuzzyJsonError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self::DeserializationMismatch {
+            message: msg.to_string(),
+            location: None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for FuzzyJsonError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::JsonError(e)
+    }
+}
+
+/// Where in the input an error was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorLocation {
+    /// Byte offset into the input.
+    pub offset: usize,
+    /// Line number, starting at 1.
+    pub line: usize,
+    /// Column in chars, starting at 1.
+    pub column: usize,
+    /// JSON Pointer to the value that was being parsed, like `/items/3/name`; empty at
+    /// the top level.
+    pub path: String,
+    /// The input line the error is on, trimmed to the part around the error, with a caret
+    /// under `column`.
+    pub snippet: String,
+}
+
+impl ErrorLocation {
+    /// Locates `offset` in `input`, the value at `path` being parsed there.
+    pub(crate) fn new(input: &str, offset: usize, path: String) -> Self {
+        let mut offset = offset.min(input.len());
+        while !input.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[offset..]
+            .find('\n')
+            .map_or(input.len(), |i| offset + i);
+        let before: Vec<char> = input[line_start..offset].chars().collect();
+        let column = before.len() + 1;
+        let line = input[..line_start].matches('\n').count() + 1;
+
+        // long single-line documents are common, so only the text around the error is shown
+        let skipped = before.len().saturating_sub(SNIPPET_CONTEXT);
+        let mut text: String = before[skipped..].iter().collect();
+        let after = &input[offset..line_end];
+        text.extend(after.chars().take(SNIPPET_CONTEXT));
+        let mut caret_at = before.len() - skipped;
+        if skipped > 0 {
+            text.insert_str(0, "...");
+            caret_at += 3;
+        }
+        if after.chars().count() > SNIPPET_CONTEXT {
+            text.push_str("...");
+        }
+        let text = text.trim_end_matches('\r');
+        let gutter = line.to_string();
+        let snippet = format!(
+            "{gutter} | {text}\n{:width$} | {:caret_at$}^",
+            "",
+            "",
+            width = gutter.len(),
+        );
+
+        Self {
+            offset,
+            line,
+            column,
+            path,
+            snippet,
+        }
+    }
+
+    /// Just the offset and path, which are cheap to take down. Handlers fail and strategies
+    /// recover from that many times over, so the line, column and snippet, which take a scan
+    /// from the start of the input, are left to `resolve` until the error reaches the caller.
+    pub(crate) fn pending(offset: usize, path: String) -> Self {
+        Self {
+            offset,
+            line: 0,
+            column: 0,
+            path,
+            snippet: String::new(),
+        }
+    }
+
+    fn resolve(&mut self, input: &str) {
+        if self.line == 0 {
+            *self = Self::new(input, self.offset, std::mem::take(&mut self.path));
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use crate::{FuzzyJsonError, FuzzyJsonParser, RepairReport};
+use serde::de::IgnoredAny;
+use serde_json::Value;
+use std::ops::Range;
+
+/// A JSON document found in free text by `FuzzyJsonParser::extract_all`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtractedDocument {
+    pub value: Value,
+    /// Byte range of the document in the input, from its opening bracket up to its closing
+    /// one, or to the end of the input when the document was truncated.
+    pub span: Range<usize>,
+    /// What `parse_with_report` would say about the text at `span`, its spans relative to
+    /// `span.start`.
+    pub report: RepairReport,
+}
+
+/// Iterator over every object or array embedded in a piece of text, see
+/// `FuzzyJsonParser::extract_iter`.
+#[derive(Debug)]
+pub struct ExtractIter<'a> {
+    parser: &'a FuzzyJsonParser,
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> ExtractIter<'a> {
+    pub(crate) fn new(parser: &'a FuzzyJsonParser, input: &'a str) -> Self {
+        Self {
+            parser,
+            input,
+            position: 0,
+        }
+    }
+}
+
+impl Iterator for ExtractIter<'_> {
+    type Item = ExtractedDocument;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(offset) = self.input[self.position..].find(['{', '[']) {
+            let start = self.position + offset;
+
+            match self.parse_candidate(start) {
+                Ok((value, report, end)) => {
+                    self.position = end;
+                    return Some(ExtractedDocument {
+                        value,
+                        span: start..end,
+                        report,
+                    });
+                }
+                // not a document after all, e.g. a stray bracket in prose; what the parse got
+                // through before it failed isn't looked at again
+                Err(stopped) => self.position = stopped.max(start + 1),
+            }
+        }
+        self.position = self.input.len();
+        None
+    }
+}
+
+impl ExtractIter<'_> {
+    /// Parses the document opening at `start`, returning it with its report and where it
+    /// ends, or where the parse stopped if it isn't one.
+    ///
+    /// The input is fed to a streaming parse in chunks that double in size, up to where
+    /// balancing brackets says the document ends, so a candidate that fails early only costs
+    /// the input read up to that point rather than everything up to its closing bracket.
+    fn parse_candidate(&self, start: usize) -> Result<(Value, RepairReport, usize), usize> {
+        let mut stream = self.parser.streaming();
+        let mut scan = DocumentEnd::default();
+        let mut from = start;
+        let mut chunk = FIRST_CHUNK;
+        let end = loop {
+            let mut to = (from + chunk).min(self.input.len());
+            while !self.input.is_char_boundary(to) {
+                to += 1;
+            }
+            let end = scan.feed(&self.input[from..to], from);
+            let fed = end.unwrap_or(to);
+            stream
+                .push(&self.input[from..fed])
+                .map_err(|e| stopped_at(&e, start, fed))?;
+            match end {
+                Some(end) => break end,
+                None if to == self.input.len() => break to,
+                None => {
+                    from = to;
+                    chunk *= 2;
+                }
+            }
+        };
+
+        let (mut value, repairs) = stream
+            .finish_with_repairs()
+            .map_err(|e| stopped_at(&e, start, end))?;
+        self.parser.validate(&mut value);
+        let report = RepairReport {
+            already_valid: serde_json::from_str::<IgnoredAny>(&self.input[start..end]).is_ok(),
+            repairs,
+        };
+        Ok((value, report, end))
+    }
+}
+
+/// Bytes of a candidate fed to its parse at first.
+const FIRST_CHUNK: usize = 64;
+
+/// Where in the input the parse of the candidate at `start` gave up, the end of what it was
+/// fed when the error doesn't say.
+fn stopped_at(error: &FuzzyJsonError, start: usize, fed: usize) -> usize {
+    error
+        .location()
+        .map_or(fed, |location| start + location.offset)
+}
+
+/// Finds where a document ends by balancing brackets outside of strings, fed the input one
+/// chunk at a time from the opening bracket on.
+///
+/// Mismatched closers (`[1, 2}`) close every scope up to the matching opener, and a document
+/// that never closes runs to the end of the input so truncation repair can take over.
+#[derive(Debug)]
+struct DocumentEnd {
+    closers: Vec<char>,
+    string_quote: Option<char>,
+    escape_next: bool,
+    previous: char,
+}
+
+impl Default for DocumentEnd {
+    fn default() -> Self {
+        Self {
+            closers: Vec::new(),
+            string_quote: None,
+            escape_next: false,
+            previous: ' ',
+        }
+    }
+}
+
+impl DocumentEnd {
+    /// Scans `text`, found at `offset` in the input, returning the offset just past the
+    /// closing bracket if the document ends in it.
+    fn feed(&mut self, text: &str, offset: usize) -> Option<usize> {
+        for (idx, ch) in text.char_indices() {
+            if let Some(quote) = self.string_quote {
+                if self.escape_next {
+                    self.escape_next = false;
+                } else if ch == '\\' {
+                    self.escape_next = true;
+                } else if ch == quote {
+                    self.string_quote = None;
+                    self.previous = quote;
+                }
+                continue;
+            }
+
+            match ch {
+                '"' => self.string_quote = Some('"'),
+                '“' => self.string_quote = Some('”'),
+                // an apostrophe only opens a string where a token may start, so prose such as
+                // `{note: it's fine}` doesn't swallow the rest of the input
+                '\'' if matches!(self.previous, '{' | '[' | ',' | ':') => {
+                    self.string_quote = Some('\'')
+                }
+                '‘' if matches!(self.previous, '{' | '[' | ',' | ':') => {
+                    self.string_quote = Some('’')
+                }
+                '{' => self.closers.push('}'),
+                '[' => self.closers.push(']'),
+                '}' | ']' => {
+                    if let Some(depth) = self.closers.iter().rposition(|closer| *closer == ch) {
+                        self.closers.truncate(depth);
+                    }
+                    if self.closers.is_empty() {
+                        return Some(offset + idx + ch.len_utf8());
+                    }
+                }
+                _ => {}
+            }
+            if !ch.is_whitespace() {
+                self.previous = ch;
+            }
+        }
+        None
+    }
+}
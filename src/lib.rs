@@ -1,16 +1,30 @@
+use path::PathTracker;
+use python::PythonString;
 use serde_json::Value;
 use std::fmt::Debug;
-use thiserror::Error;
-
-#[derive(Error, Debug)]
-pub enum FuzzyJsonError {
-    #[error("Invalid JSON at position {pos}: {msg}")]
-    ParseError { pos: usize, msg: String },
-    #[error("Repair failed: {0}")]
-    RepairFailed(String),
-    #[error("JSON error: {0}")]
-    JsonError(#[from] serde_json::Error),
-}
+use std::time::{Duration, Instant};
+
+mod de;
+mod error;
+mod extract;
+mod partial;
+pub mod patch;
+mod path;
+mod python;
+mod report;
+mod schema;
+mod shape;
+mod streaming;
+
+pub use de::{Deserializer, from_str};
+pub use error::{ErrorLocation, FuzzyJsonError};
+pub use extract::{ExtractIter, ExtractedDocument};
+pub use patch::PatchOperation;
+pub use python::{PythonLiteralHandler, PythonStringHandler, TupleHandler};
+pub use report::{RepairRecord, RepairReport};
+pub use schema::SchemaViolation;
+pub use shape::{ExpectedShape, Shape, ShapeMismatch};
+pub use streaming::StreamingParser;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum JsonContext {
@@ -34,12 +48,34 @@ impl JsonContext {
     }
 }
 
+/// Cursor over the input being repaired.
+///
+/// `position` is a byte offset into `input` and always sits on a char boundary, so every
+/// accessor below is O(1) (or O(count) for `peek_chars`/`advance`) instead of re-walking the
+/// input from the start.
 #[derive(Debug, Clone)]
 pub struct ParseState {
     pub input: String,
     pub position: usize,
     pub stack: Vec<JsonContext>,
     pub output: String,
+    pub repairs: Vec<RepairRecord>,
+    /// Delimiter of a string literal the input ran out in the middle of.
+    pub open_string: Option<char>,
+    /// The Python literal `open_string` belongs to, see `PythonStringHandler`.
+    python_string: Option<PythonString>,
+    path_tracker: PathTracker,
+    budget: Budget,
+}
+
+/// What repairing the input has used up, checked against the limits in `ParserOptions`.
+/// Rewinding a step gives back the repair attempts it was charged, since it is taken again
+/// once more input arrives, but not the steps and time it took.
+#[derive(Debug, Clone, Default)]
+struct Budget {
+    steps: usize,
+    repairs: usize,
+    spent: Duration,
 }
 
 impl ParseState {
@@ -49,33 +85,37 @@ impl ParseState {
             position: 0,
             stack: vec![JsonContext::Root],
             output: String::new(),
+            repairs: Vec::new(),
+            open_string: None,
+            python_string: None,
+            path_tracker: PathTracker::default(),
+            budget: Budget::default(),
         }
     }
 
     pub fn current_char(&self) -> Option<char> {
-        self.input.chars().nth(self.position)
+        self.remaining().chars().next()
     }
 
     pub fn peek_chars(&self, count: usize) -> String {
-        self.input.chars().skip(self.position).take(count).collect()
+        self.remaining().chars().take(count).collect()
     }
 
-    pub fn advance(&mut self, count: usize) -> String {
-        let chars: String = self.input.chars().skip(self.position).take(count).collect();
-        self.position += count;
-        chars
+    /// Moves the cursor forward by `count` chars (not bytes) and returns the skipped slice.
+    pub fn advance(&mut self, count: usize) -> &str {
+        let start = self.position;
+        let skipped: usize = self
+            .remaining()
+            .chars()
+            .take(count)
+            .map(char::len_utf8)
+            .sum();
+        self.position += skipped;
+        &self.input[start..self.position]
     }
 
     pub fn remaining(&self) -> &str {
-        match self
-            .input
-            .char_indices()
-            .nth(self.position)
-            .map(|(idx, _)| idx)
-        {
-            Some(start_byte) => &self.input[start_byte..],
-            None => "",
-        }
+        self.input.get(self.position..).unwrap_or("")
     }
 
     pub fn is_sq_key_or_value(&self) -> bool {
@@ -109,13 +149,53 @@ impl ParseState {
     }
 
     pub fn is_finished(&self) -> bool {
-        self.position >= self.input.chars().count()
+        self.position >= self.input.len()
+    }
+
+    /// JSON Pointer to the value being parsed, like `/items/3/name`, or `""` at the top
+    /// level. Inside an object but between keys it points at the object itself.
+    pub fn path(&self) -> String {
+        self.path_tracker.pointer(&self.output)
+    }
+
+    /// Where the cursor is, for the errors handlers and strategies return. Only the offset
+    /// and path are taken down here; the line, column and snippet are filled in by
+    /// `locate` if the error makes it out of the repair.
+    pub fn error_location(&self) -> ErrorLocation {
+        ErrorLocation::pending(self.position, self.path())
+    }
+
+    /// Finishes the location of an error that is about to be returned to the caller.
+    pub(crate) fn locate(&self, error: FuzzyJsonError) -> FuzzyJsonError {
+        error.resolve(&self.input)
+    }
+
+    /// Parses the repaired output, blaming the input for ending early when it left a scope
+    /// open.
+    pub(crate) fn output_value(&self) -> Result<Value, FuzzyJsonError> {
+        serde_json::from_str(&self.output).map_err(|e| {
+            if self.current_context() == &JsonContext::Root {
+                FuzzyJsonError::JsonError(e)
+            } else {
+                self.locate(FuzzyJsonError::UnexpectedEnd {
+                    location: self.error_location(),
+                })
+            }
+        })
     }
 
     pub fn current_context(&self) -> &JsonContext {
         self.stack.last().unwrap_or(&JsonContext::Root)
     }
 
+    /// Objects and arrays open on the stack.
+    pub fn depth(&self) -> usize {
+        self.stack
+            .iter()
+            .filter(|context| matches!(context, JsonContext::Object | JsonContext::Array))
+            .count()
+    }
+
     pub fn push_context(&mut self, context: JsonContext) {
         self.stack.push(context);
     }
@@ -127,6 +207,132 @@ impl ParseState {
             None
         }
     }
+
+    /// Marks the output the next repair may touch.
+    ///
+    /// Handlers and repairs only ever append to the output or take back trailing whitespace
+    /// and commas, so remembering that tail is enough to tell what a repair removed and
+    /// inserted, or to undo a step.
+    fn output_mark(&self) -> OutputMark {
+        let kept = self
+            .output
+            .trim_end_matches(|c: char| c.is_whitespace() || c == ',')
+            .len();
+        OutputMark {
+            position: self.position,
+            kept,
+            tail: self.output[kept..].to_string(),
+        }
+    }
+
+    fn record_repair(
+        &mut self,
+        strategy: &'static str,
+        lossy: bool,
+        mark: OutputMark,
+        path: String,
+    ) {
+        let kept = mark.kept.min(self.output.len());
+        let new_tail = self.output.get(kept..).unwrap_or("");
+        let common: usize = mark
+            .tail
+            .chars()
+            .zip(new_tail.chars())
+            .take_while(|(old, new)| old == new)
+            .map(|(old, _)| old.len_utf8())
+            .sum();
+
+        let mut removed = mark.tail[common..].to_string();
+        removed.push_str(&self.input[mark.position..self.position]);
+        let inserted = new_tail[common..].to_string();
+        if removed.is_empty() && inserted.is_empty() {
+            return;
+        }
+
+        self.repairs.push(RepairRecord {
+            strategy,
+            span: mark.position..self.position,
+            removed,
+            inserted,
+            lossy,
+            path,
+        });
+    }
+}
+
+#[derive(Debug)]
+struct OutputMark {
+    position: usize,
+    kept: usize,
+    tail: String,
+}
+
+/// Everything needed to undo a single step, see `ParseState::rewind`.
+#[derive(Debug)]
+struct Checkpoint {
+    mark: OutputMark,
+    stack: Vec<JsonContext>,
+    open_string: Option<char>,
+    python_string: Option<PythonString>,
+    repairs: usize,
+    attempts: usize,
+}
+
+impl ParseState {
+    /// The path tracker is brought up to the marked output here rather than copied: a rewind
+    /// only ever takes back output past the mark, which it hasn't scanned.
+    fn checkpoint(&mut self) -> Checkpoint {
+        self.path_tracker.sync(&self.output);
+        Checkpoint {
+            mark: self.output_mark(),
+            stack: self.stack.clone(),
+            open_string: self.open_string,
+            python_string: self.python_string,
+            repairs: self.repairs.len(),
+            attempts: self.budget.repairs,
+        }
+    }
+
+    fn rewind(&mut self, checkpoint: Checkpoint) {
+        self.output
+            .truncate(checkpoint.mark.kept.min(self.output.len()));
+        self.output.push_str(&checkpoint.mark.tail);
+        self.position = checkpoint.mark.position;
+        self.stack = checkpoint.stack;
+        self.open_string = checkpoint.open_string;
+        self.python_string = checkpoint.python_string;
+        self.repairs.truncate(checkpoint.repairs);
+        self.budget.repairs = checkpoint.attempts;
+    }
+}
+
+/// Everything needed to undo any number of steps, see `ParseState::restore`.
+#[derive(Debug)]
+pub(crate) struct SavedState {
+    checkpoint: Checkpoint,
+    input: usize,
+    path_tracker: PathTracker,
+    budget: Budget,
+}
+
+impl ParseState {
+    /// Like `checkpoint`, but for trying out any number of steps: the input appended for
+    /// them, the path tracker and the budget they used are put back as well.
+    pub(crate) fn save(&mut self) -> SavedState {
+        SavedState {
+            checkpoint: self.checkpoint(),
+            input: self.input.len(),
+            path_tracker: self.path_tracker.clone(),
+            budget: self.budget.clone(),
+        }
+    }
+
+    pub(crate) fn restore(&mut self, saved: SavedState) {
+        self.rewind(saved.checkpoint);
+        self.input.truncate(saved.input);
+        self.path_tracker = saved.path_tracker;
+        self.budget = saved.budget;
+    }
 }
 
 pub trait RepairStrategy: Send + Sync + Debug {
@@ -134,6 +340,12 @@ pub trait RepairStrategy: Send + Sync + Debug {
     fn can_repair(&self, state: &ParseState, error: &str) -> bool;
     fn repair(&self, state: &mut ParseState, error: &str) -> Result<(), FuzzyJsonError>;
     fn priority(&self) -> u8; // Higher priority strategies are tried first
+
+    /// Whether repairing the current state would discard input or invent values.
+    /// `strict_mode` refuses such repairs instead of applying them.
+    fn is_lossy(&self, _state: &ParseState, _error: &str) -> bool {
+        false
+    }
 }
 
 pub trait StateHandler: Send + Sync + Debug {
@@ -148,16 +360,65 @@ pub struct FuzzyJsonParser {
     options: ParserOptions,
 }
 
+/// What `NaN`, `Infinity` and `-Infinity`, which JSON has no numbers for, turn into.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NonFiniteNumbers {
+    /// `null`, reported as a lossy repair.
+    #[default]
+    Null,
+    /// Their names as strings, e.g. `"-Infinity"`.
+    String,
+}
+
+impl std::str::FromStr for NonFiniteNumbers {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "null" => Ok(Self::Null),
+            "string" => Ok(Self::String),
+            _ => Err(format!("expected `null` or `string`, got `{s}`")),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ParserOptions {
     pub auto_repair: bool,
     pub allow_trailing_commas: bool,
     pub allow_comments: bool,
     pub allow_single_quotes: bool,
+    /// Accept JavaScript-style unquoted object keys, see `NoQuotesKeyHandler`.
     pub allow_unquoted_keys: bool,
+    /// Characters unquoted keys may contain on top of an ECMAScript IdentifierName's.
+    pub unquoted_key_chars: String,
+    /// Accept unquoted string values such as `{status: active}`.
+    pub allow_bareword_values: bool,
+    /// Most repairs one document may need. Steps that parse input as it is don't count.
     pub max_repair_attempts: usize,
     pub strict_mode: bool,
     pub aggressive_truncation_repair: bool, // New option for LLM truncation handling
+    /// Accept `` `backtick` `` quoted strings.
+    pub allow_backtick_strings: bool,
+    pub non_finite_numbers: NonFiniteNumbers,
+    /// Accept Python literals: `True`/`False`/`None`, tuples and `r''`, `u''` or
+    /// triple-quoted strings.
+    pub allow_python_literals: bool,
+    /// JSON Schema the repaired value is validated against and fixed up to match.
+    pub schema: Option<Value>,
+    /// Longest input, in bytes, to even look at.
+    pub max_input_bytes: Option<usize>,
+    /// Deepest objects and arrays may nest.
+    pub max_depth: Option<usize>,
+    /// Longest string, in bytes of the repaired output, keys included.
+    pub max_string_length: Option<usize>,
+    /// Most keys an object, or elements an array, may have.
+    pub max_entries: Option<usize>,
+    /// Most handler steps to spend repairing one document.
+    pub max_steps: Option<usize>,
+    /// Most time to spend repairing one document. Time a stream spends waiting for its next
+    /// chunk doesn't count.
+    pub max_duration: Option<Duration>,
 }
 
 impl Default for ParserOptions {
@@ -167,10 +428,22 @@ impl Default for ParserOptions {
             allow_trailing_commas: true,
             allow_comments: true,
             allow_single_quotes: true,
-            allow_unquoted_keys: false,
+            allow_unquoted_keys: true,
+            unquoted_key_chars: "-.".to_string(),
+            allow_bareword_values: false,
             max_repair_attempts: 1500,
             strict_mode: false,
             aggressive_truncation_repair: true, // Enable by default for LLM responses
+            allow_backtick_strings: false,
+            non_finite_numbers: NonFiniteNumbers::Null,
+            allow_python_literals: false,
+            schema: None,
+            max_input_bytes: None,
+            max_depth: None,
+            max_string_length: None,
+            max_entries: None,
+            max_steps: None,
+            max_duration: None,
         }
     }
 }
@@ -296,9 +569,8 @@ impl FuzzyJsonParser {
         }
 
         // Remove any trailing comma that might cause issues
-        let trimmed_output = state.output.trim_end();
-        if trimmed_output.ends_with(',') {
-            state.output = trimmed_output[..trimmed_output.len() - 1].to_string();
+        if let Some(trimmed_output) = state.output.trim_end().strip_suffix(',') {
+            state.output = trimmed_output.to_string();
         }
 
         // Close scopes in reverse order (LIFO)
@@ -335,25 +607,113 @@ impl FuzzyJsonParser {
         self.repair_strategies.push(strategy);
         // Sort by priority (highest first)
         self.repair_strategies
-            .sort_by(|a, b| b.priority().cmp(&a.priority()));
+            .sort_by_key(|strategy| std::cmp::Reverse(strategy.priority()));
     }
 
     pub fn register_handler(&mut self, handler: Box<dyn StateHandler>) {
         self.state_handlers.push(handler);
     }
 
-    pub fn parse<T>(&self, json_str: &str) -> Result<T, FuzzyJsonError>
+    /// Repairs `json_str` into a `T`, borrowing `&str` fields from the input where the
+    /// repair left them alone. See `Deserializer`.
+    pub fn parse<'de, T>(&self, json_str: &'de str) -> Result<T, FuzzyJsonError>
     where
-        T: serde::de::DeserializeOwned,
+        T: serde::Deserialize<'de>,
+    {
+        if self.options.schema.is_some() {
+            // the schema fix-ups need the whole value
+            let value = self.parse_value(json_str)?;
+            return T::deserialize(value).map_err(FuzzyJsonError::mismatch);
+        }
+        self.check_input_size(json_str.len())?;
+        let mut deserializer = match serde_json::from_str(json_str) {
+            Ok(value) => {
+                self.check_valid(json_str)?;
+                return Ok(value);
+            }
+            Err(e) => Deserializer::after_failure(self, json_str, e)?,
+        };
+        let value = T::deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        Ok(value)
+    }
+
+    /// Like `parse`, but lets the shape of `T` guide the repair: fields that are missing or
+    /// were cut off get type defaults, `"42"` becomes `42` where a number is expected, a lone
+    /// object is wrapped where a `Vec` is expected, and unknown keys are dropped.
+    pub fn parse_guided<T>(&self, json_str: &str) -> Result<T, FuzzyJsonError>
+    where
+        T: serde::de::DeserializeOwned + ExpectedShape,
     {
-        let value = self.parse_value(json_str)?;
-        serde_json::from_value(value).map_err(FuzzyJsonError::JsonError)
+        let (value, mismatches) = self.parse_with_shape(json_str, &T::shape())?;
+        if let Some(mismatch) = mismatches.first() {
+            return Err(FuzzyJsonError::DeserializationMismatch {
+                message: mismatch.to_string(),
+                location: None,
+            });
+        }
+        serde_json::from_value(value).map_err(FuzzyJsonError::mismatch)
+    }
+
+    /// Repairs `json_str` and bends the result into `shape`, see `Shape::conform`, returning
+    /// the values that don't fit along with it. Values the repair made up get the default
+    /// when they don't fit.
+    pub fn parse_with_shape(
+        &self,
+        json_str: &str,
+        shape: &Shape,
+    ) -> Result<(Value, Vec<ShapeMismatch>), FuzzyJsonError> {
+        let (value, report) = self.parse_with_report(json_str)?;
+        let invented: Vec<&str> = report
+            .repairs
+            .iter()
+            .filter(|record| record.lossy && !record.inserted.is_empty())
+            .map(|record| record.path.as_str())
+            .collect();
+        Ok(shape.conform_repaired(value, &invented))
     }
 
     pub fn parse_value(&self, json_str: &str) -> Result<Value, FuzzyJsonError> {
+        self.parse_with_report(json_str).map(|(value, _)| value)
+    }
+
+    /// Like `parse_value`, but also returns the ways the value still breaks the schema set
+    /// with `FuzzyJsonParserBuilder::with_schema` once it has been fixed up. Without a schema
+    /// there are never any violations.
+    pub fn parse_validated(
+        &self,
+        json_str: &str,
+    ) -> Result<(Value, Vec<SchemaViolation>), FuzzyJsonError> {
+        let (mut value, _) = self.repair_with_report(json_str)?;
+        let violations = self.validate(&mut value);
+        Ok((value, violations))
+    }
+
+    /// Like `parse_value`, but also reports every repair that was needed to get there.
+    pub fn parse_with_report(
+        &self,
+        json_str: &str,
+    ) -> Result<(Value, RepairReport), FuzzyJsonError> {
+        let (mut value, report) = self.repair_with_report(json_str)?;
+        self.validate(&mut value);
+        Ok((value, report))
+    }
+
+    pub(crate) fn validate(&self, value: &mut Value) -> Vec<SchemaViolation> {
+        match &self.options.schema {
+            Some(schema) => schema::validate(schema, value),
+            None => Vec::new(),
+        }
+    }
+
+    fn repair_with_report(&self, json_str: &str) -> Result<(Value, RepairReport), FuzzyJsonError> {
+        self.check_input_size(json_str.len())?;
         // First try standard parsing
         match serde_json::from_str(json_str) {
-            Ok(value) => Ok(value),
+            Ok(value) => {
+                self.check_valid(json_str)?;
+                Ok((value, RepairReport::valid()))
+            }
             Err(e) => {
                 if !self.options.auto_repair {
                     return Err(FuzzyJsonError::RepairFailed(
@@ -362,21 +722,69 @@ impl FuzzyJsonParser {
                 }
 
                 // Try fuzzy parsing with repair
-                let repaired = self.repair_json(json_str, e)?;
-                serde_json::from_str(&repaired).map_err(FuzzyJsonError::JsonError)
+                let state = self.repair_state(json_str, e)?;
+                let value = state.output_value()?;
+                let report = RepairReport {
+                    already_valid: false,
+                    repairs: state.repairs,
+                };
+                Ok((value, report))
             }
         }
     }
 
+    /// Starts an incremental parse for input that arrives in chunks, e.g. streamed LLM tokens.
+    pub fn streaming(&self) -> StreamingParser<'_> {
+        StreamingParser::new(self)
+    }
+
+    /// Finds every JSON object or array embedded in free text (several fenced blocks in one
+    /// LLM answer, say) and repairs each of them independently.
+    pub fn extract_all(&self, input: &str) -> Vec<ExtractedDocument> {
+        self.extract_iter(input).collect()
+    }
+
+    /// Lazy version of `extract_all`.
+    pub fn extract_iter<'a>(&'a self, input: &'a str) -> ExtractIter<'a> {
+        ExtractIter::new(self, input)
+    }
+
     pub fn repair_json(
         &self,
         json_str: &str,
         e: serde_json::error::Error,
     ) -> Result<String, FuzzyJsonError> {
-        let mut state = ParseState::new(json_str.trim().to_string());
-        let mut attempts = 0;
+        self.repair_state(json_str, e).map(|state| state.output)
+    }
+
+    fn repair_state(
+        &self,
+        json_str: &str,
+        e: serde_json::error::Error,
+    ) -> Result<ParseState, FuzzyJsonError> {
+        let error = e.to_string();
+        let mut state = self.start_repair(json_str, &error)?;
+
+        self.run_to_end(&mut state, &error)?;
+        // #[cfg(debug_assertions)]
+        // println!("Output: {:?}", state.output);
+        Ok(state)
+    }
 
-        self.try_repair_strategies(&mut state, &e.to_string())?;
+    /// State to repair `json_str` from, with `error` being why it didn't parse as is.
+    pub(crate) fn start_repair(
+        &self,
+        json_str: &str,
+        error: &str,
+    ) -> Result<ParseState, FuzzyJsonError> {
+        self.check_input_size(json_str.len())?;
+        // trailing whitespace would hide truncation, leading whitespace is skipped rather than
+        // trimmed so that positions stay byte offsets into `json_str`
+        let mut state = ParseState::new(json_str.trim_end().to_string());
+        state.position = (json_str.len() - json_str.trim_start().len()).min(state.input.len());
+
+        self.try_repair_strategies(&mut state, error)
+            .map_err(|e| state.locate(e))?;
         // try repairing once
         /*
         println!(
@@ -384,77 +792,35 @@ impl FuzzyJsonParser {
             repair_successful, state
         );*/
         // .context("Failed to repair json using available repair strategies")?;
+        Ok(state)
+    }
 
-        while !state.is_finished() && attempts < self.options.max_repair_attempts {
-            let mut handled = false;
-
-            // Try state handlers first
-            for handler in &self.state_handlers {
-                if handler.can_handle(&state) {
-                    /*
-                    #[cfg(debug_assertions)]
-                    println!(
-                        "State: {:?} | {:?} : {:?} | {:?} | Handler: {:?} | Context: {:?}",
-                        state.position,
-                        state.current_char(),
-                        state.remaining().chars().nth(0),
-                        state.output,
-                        handler,
-                        state.current_context()
-                    );*/
-                    match handler.handle(&mut state) {
-                        Ok(should_continue) => {
-                            handled = true;
-                            if !should_continue {
-                                return Ok(state.output);
-                            }
-                            break;
-                        }
-                        Err(e) => {
-                            // println!("State(e): {:?}", e);
-                            // Try repair strategies
-                            if self.try_repair_strategies(&mut state, &e.to_string())? {
-                                handled = true;
-                                break;
-                            }
-                        }
-                    }
-                } else {
-                    /*
-                    #[cfg(debug_assertions)]
-                    println!(
-                        "Can't handle |  handler: {:?} | State(e): {:?} | Remaining First Char: {:?} |  Current Char: {:?}",
-                        handler,
-                        state.position,
-                        state.remaining().chars().nth(0),
-                        state.current_char()
-                    );*/
-                }
-            }
-            if !handled {
-                /* println!(
-                    "Not handled |  output: {:?} | State(e): {:?} | Current Char: {:?}",
-                    state.output,
-                    state.position,
-                    state.current_char()
-                );*/
-                if self.try_repair_strategies(&mut state, &e.to_string())? {
-                    handled = true;
-                }
-            }
+    /// Steps through the rest of the input, then closes whatever the input left open.
+    fn run_to_end(&self, state: &mut ParseState, error: &str) -> Result<(), FuzzyJsonError> {
+        while self.run_step(state, error)? {}
+        Ok(())
+    }
 
-            if !handled {
-                return Err(FuzzyJsonError::ParseError {
-                    pos: state.position,
-                    msg: format!(
-                        "No handler for current state: {:?} | {:?}",
-                        state.current_context(),
-                        state.current_char()
-                    ),
-                });
-            }
+    /// Does the next bit of `run_to_end`'s work: one step while there is input left, then
+    /// closing whatever the input left open. Returns false once there is nothing left to do.
+    pub(crate) fn run_step(
+        &self,
+        state: &mut ParseState,
+        error: &str,
+    ) -> Result<bool, FuzzyJsonError> {
+        self.run_step_unlocated(state, error)
+            .map_err(|e| state.locate(e))
+    }
 
-            attempts += 1;
+    /// `run_step` for when the error will likely be recovered from, see
+    /// `ErrorLocation::pending`.
+    pub(crate) fn run_step_unlocated(
+        &self,
+        state: &mut ParseState,
+        error: &str,
+    ) -> Result<bool, FuzzyJsonError> {
+        if !state.is_finished() {
+            return self.step(state, error);
         }
         /*
         println!(
@@ -471,18 +837,200 @@ impl FuzzyJsonParser {
                 state.output,
                 state.current_context()
             );*/
-            self.try_repair_strategies(&mut state, &e.to_string())?;
+            self.try_repair_strategies(state, error)?;
         }
+        self.check_limits(state)?;
+        Ok(false)
+    }
 
-        if attempts >= self.options.max_repair_attempts {
-            return Err(FuzzyJsonError::RepairFailed(
-                "Too many repair attempts".to_string(),
-            ));
+    fn check_input_size(&self, size: usize) -> Result<(), FuzzyJsonError> {
+        match self.options.max_input_bytes {
+            Some(limit) if size > limit => Err(FuzzyJsonError::InputTooLarge { size, limit }),
+            _ => Ok(()),
         }
+    }
 
-        // #[cfg(debug_assertions)]
-        // println!("Output: {:?}", state.output);
-        Ok(state.output)
+    /// Holds input that parsed as is to the limits repair checks as it goes.
+    fn check_valid(&self, json_str: &str) -> Result<(), FuzzyJsonError> {
+        let options = &self.options;
+        if options.max_depth.is_none()
+            && options.max_string_length.is_none()
+            && options.max_entries.is_none()
+        {
+            return Ok(());
+        }
+        let mut tracker = PathTracker::default();
+        tracker.sync(json_str);
+        let extremes = tracker.extremes();
+        let exceeded = |limit: Option<usize>, peak: path::Peak| {
+            let limit = limit.filter(|&limit| peak.value > limit)?;
+            let path = PathTracker::default().pointer(&json_str[..peak.offset]);
+            Some((limit, ErrorLocation::new(json_str, peak.offset, path)))
+        };
+        if let Some((limit, location)) = exceeded(options.max_depth, extremes.depth) {
+            return Err(FuzzyJsonError::NestingTooDeep { limit, location });
+        }
+        if let Some((limit, location)) = exceeded(options.max_string_length, extremes.string_length)
+        {
+            return Err(FuzzyJsonError::StringTooLong { limit, location });
+        }
+        if let Some((limit, location)) = exceeded(options.max_entries, extremes.entries) {
+            return Err(FuzzyJsonError::TooManyEntries { limit, location });
+        }
+        Ok(())
+    }
+
+    /// Fails once the repair has gone past one of the limits in `ParserOptions`.
+    pub(crate) fn check_limits(&self, state: &mut ParseState) -> Result<(), FuzzyJsonError> {
+        state.path_tracker.sync(&state.output);
+        let options = &self.options;
+        let budget = &state.budget;
+        if budget.repairs > options.max_repair_attempts {
+            return Err(FuzzyJsonError::ExceededRepairAttempts {
+                attempts: budget.repairs,
+                location: state.error_location(),
+            });
+        }
+        if let Some(limit) = options.max_steps
+            && budget.steps > limit
+        {
+            return Err(FuzzyJsonError::ExceededStepBudget {
+                steps: budget.steps,
+                location: state.error_location(),
+            });
+        }
+        if let Some(limit) = options.max_duration
+            && budget.spent > limit
+        {
+            return Err(FuzzyJsonError::ExceededTimeBudget {
+                elapsed: budget.spent,
+                location: state.error_location(),
+            });
+        }
+        if let Some(limit) = options.max_depth
+            && state.depth() > limit
+        {
+            return Err(FuzzyJsonError::NestingTooDeep {
+                limit,
+                location: state.error_location(),
+            });
+        }
+        let extremes = state.path_tracker.extremes();
+        if let Some(limit) = options.max_string_length
+            && extremes.string_length.value > limit
+        {
+            return Err(FuzzyJsonError::StringTooLong {
+                limit,
+                location: state.error_location(),
+            });
+        }
+        if let Some(limit) = options.max_entries
+            && extremes.entries.value > limit
+        {
+            return Err(FuzzyJsonError::TooManyEntries {
+                limit,
+                location: state.error_location(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Runs the next handler or repair, charging it to the state's budget. A step that
+    /// consumed no input counts as a repair attempt even if no strategy ran, so that a
+    /// handler stuck in place can't loop forever.
+    fn step(&self, state: &mut ParseState, error: &str) -> Result<bool, FuzzyJsonError> {
+        self.check_limits(state)?;
+        let position = state.position;
+        let repairs = state.budget.repairs;
+        let started = self.options.max_duration.map(|_| Instant::now());
+
+        let result = self.handle_next(state, error);
+
+        state.budget.steps += 1;
+        if let Some(started) = started {
+            state.budget.spent += started.elapsed();
+        }
+        if state.position == position && state.budget.repairs == repairs {
+            state.budget.repairs += 1;
+        }
+        result
+    }
+
+    /// Lets the first matching handler consume the next token, falling back to the repair
+    /// strategies. Returns false once a handler asks for parsing to stop.
+    fn handle_next(&self, state: &mut ParseState, error: &str) -> Result<bool, FuzzyJsonError> {
+        // a string cut off by the end of a streamed chunk picks up where it stopped
+        if state.open_string.is_some() {
+            let allow_comments = self.options.allow_comments;
+            let resumed = match state.python_string {
+                Some(_) => PythonStringHandler { allow_comments }.handle(state),
+                None => StringHandler {
+                    allow_comments,
+                    allow_single_quotes: self.options.allow_single_quotes,
+                }
+                .handle(state),
+            };
+            return match resumed {
+                Err(e) if self.try_repair_strategies(state, &e.headline())? => Ok(true),
+                result => result,
+            };
+        }
+
+        // only repair strategies would drop it, and which one was meant can't be told
+        if state.current_context() == &JsonContext::Root
+            && !state.output.is_empty()
+            && matches!(state.current_char(), Some('{' | '['))
+        {
+            return Err(FuzzyJsonError::AmbiguousDocuments {
+                location: state.error_location(),
+            });
+        }
+
+        let mut failure = None;
+
+        // Try state handlers first
+        for handler in &self.state_handlers {
+            if handler.can_handle(state) {
+                /*
+                #[cfg(debug_assertions)]
+                println!(
+                    "State: {:?} | {:?} : {:?} | {:?} | Handler: {:?} | Context: {:?}",
+                    state.position,
+                    state.current_char(),
+                    state.remaining().chars().nth(0),
+                    state.output,
+                    handler,
+                    state.current_context()
+                );*/
+                match handler.handle(state) {
+                    Ok(should_continue) => return Ok(should_continue),
+                    Err(e) => {
+                        // println!("State(e): {:?}", e);
+                        // Try repair strategies
+                        if self.try_repair_strategies(state, &e.headline())? {
+                            return Ok(true);
+                        }
+                        failure.get_or_insert(e);
+                    }
+                }
+            }
+        }
+        /* println!(
+            "Not handled |  output: {:?} | State(e): {:?} | Current Char: {:?}",
+            state.output,
+            state.position,
+            state.current_char()
+        );*/
+        if self.try_repair_strategies(state, error)? {
+            return Ok(true);
+        }
+
+        let location = state.error_location();
+        Err(failure.unwrap_or(match state.current_char() {
+            Some(found @ ('}' | ']')) => FuzzyJsonError::UnbalancedClose { found, location },
+            Some(found) => FuzzyJsonError::UnexpectedCharacter { found, location },
+            None => FuzzyJsonError::UnexpectedEnd { location },
+        }))
     }
 
     fn try_repair_strategies(
@@ -493,9 +1041,20 @@ impl FuzzyJsonParser {
         // println!("COntext: {:?} | Is key: {:?}", state.current_context(), state.is_prop());
         for strategy in &self.repair_strategies {
             if strategy.can_repair(state, error) {
+                let lossy = strategy.is_lossy(state, error);
+                if self.options.strict_mode && lossy {
+                    return Err(FuzzyJsonError::StrictModeViolation {
+                        strategy: strategy.name(),
+                        location: state.error_location(),
+                    });
+                }
                 // #[cfg(debug_assertions)]
                 // println!("Repaired using {:?} | output: {}", strategy, state.output);
+                let mark = state.output_mark();
+                let path = state.path();
+                state.budget.repairs += 1;
                 strategy.repair(state, error)?;
+                state.record_repair(strategy.name(), lossy, mark, path);
                 return Ok(true);
             }
         }
@@ -504,27 +1063,72 @@ impl FuzzyJsonParser {
 
     fn register_default_strategies(&mut self) {
         self.register_strategy(Box::new(TruncationRepairStrategy));
-        self.register_strategy(Box::new(SingleQuotesStrategy));
+        if self.options.allow_single_quotes {
+            self.register_strategy(Box::new(SingleQuotesStrategy));
+        }
+        self.register_strategy(Box::new(InvalidEscapeStrategy));
         self.register_strategy(Box::new(CodeBlockMarkersStrategy));
         self.register_strategy(Box::new(IncompletePropertyStrategy));
         self.register_strategy(Box::new(IncompleteArrayStrategy));
-        self.register_strategy(Box::new(TrailingCommaStrategy));
+        if self.options.allow_trailing_commas {
+            self.register_strategy(Box::new(TrailingCommaStrategy));
+        }
+        self.register_strategy(Box::new(MissingCommaStrategy));
+        self.register_strategy(Box::new(NonFiniteNumberStrategy {
+            policy: self.options.non_finite_numbers,
+        }));
+        self.register_strategy(Box::new(BareSignStrategy));
         self.register_strategy(Box::new(MissingQuotesStrategy));
         self.register_strategy(Box::new(MissingBracketsStrategy));
-        self.register_strategy(Box::new(TrimStrayContentInBeginningStrategy));
+        self.register_strategy(Box::new(TrimStrayContentInBeginningStrategy {
+            allow_tuples: self.options.allow_python_literals,
+        }));
         self.register_strategy(Box::new(TrimStrayContentInEndStrategy));
     }
 
     fn register_default_handlers(&mut self) {
         self.register_handler(Box::new(WhitespaceHandler));
+        if self.options.allow_comments {
+            self.register_handler(Box::new(CommentHandler));
+        }
+        if self.options.allow_python_literals {
+            self.register_handler(Box::new(PythonLiteralHandler));
+            self.register_handler(Box::new(TupleHandler));
+            self.register_handler(Box::new(PythonStringHandler {
+                allow_comments: self.options.allow_comments,
+            }));
+        }
         self.register_handler(Box::new(LiteralHandler));
         self.register_handler(Box::new(ColonHandler));
-        self.register_handler(Box::new(CommaHandler));
-        self.register_handler(Box::new(StringHandler));
+        self.register_handler(Box::new(CommaHandler {
+            allow_trailing_commas: self.options.allow_trailing_commas,
+        }));
+        self.register_handler(Box::new(StringHandler {
+            allow_comments: self.options.allow_comments,
+            allow_single_quotes: self.options.allow_single_quotes,
+        }));
+        if self.options.allow_backtick_strings {
+            self.register_handler(Box::new(BacktickStringHandler {
+                allow_comments: self.options.allow_comments,
+            }));
+        }
         self.register_handler(Box::new(NumberHandler));
-        self.register_handler(Box::new(ObjectHandler));
-        self.register_handler(Box::new(ArrayHandler));
-        self.register_handler(Box::new(NoQuotesKeyHandler));
+        self.register_handler(Box::new(ObjectHandler {
+            allow_trailing_commas: self.options.allow_trailing_commas,
+        }));
+        self.register_handler(Box::new(ArrayHandler {
+            allow_trailing_commas: self.options.allow_trailing_commas,
+        }));
+        if self.options.allow_unquoted_keys {
+            self.register_handler(Box::new(NoQuotesKeyHandler {
+                extra_chars: self.options.unquoted_key_chars.clone(),
+            }));
+        }
+        if self.options.allow_bareword_values {
+            self.register_handler(Box::new(BarewordValueHandler {
+                allow_comments: self.options.allow_comments,
+            }));
+        }
     }
 }
 
@@ -547,7 +1151,7 @@ impl RepairStrategy for TrailingCommaStrategy {
                     .peek_chars(2)
                     .chars()
                     .nth(1)
-                    .map_or(false, |next| next == '}' || next == ']')
+                    .is_some_and(|next| next == '}' || next == ']')
         } else {
             false
         }
@@ -560,6 +1164,47 @@ impl RepairStrategy for TrailingCommaStrategy {
     }
 }
 
+#[derive(Debug)]
+pub struct MissingCommaStrategy;
+
+impl MissingCommaStrategy {
+    /// Whether a string or unquoted key starts right after another array element or object
+    /// member, as in `["a" "b"]` and `{a: 'x' b: 'y'}`.
+    fn comma_missing(state: &ParseState) -> bool {
+        let starts_entry = match state.current_context() {
+            JsonContext::Array => false,
+            JsonContext::Object => starts_unquoted_key(state.remaining()),
+            _ => return false,
+        };
+        (starts_entry
+            || state
+                .current_char()
+                .is_some_and(|ch| matches!(ch, '"' | '\'' | '“' | '‘')))
+            && state
+                .output
+                .trim_end()
+                .ends_with(|c: char| matches!(c, '"' | '}' | ']') || c.is_ascii_alphanumeric())
+    }
+}
+
+impl RepairStrategy for MissingCommaStrategy {
+    fn name(&self) -> &'static str {
+        "missing_comma"
+    }
+    fn priority(&self) -> u8 {
+        90
+    }
+
+    fn can_repair(&self, state: &ParseState, _error: &str) -> bool {
+        Self::comma_missing(state)
+    }
+
+    fn repair(&self, state: &mut ParseState, _error: &str) -> Result<(), FuzzyJsonError> {
+        state.output.push(',');
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub struct MissingQuotesStrategy;
 
@@ -575,11 +1220,10 @@ impl RepairStrategy for MissingQuotesStrategy {
     fn can_repair(&self, state: &ParseState, error: &str) -> bool {
         error.contains("expected") && error.contains("quote")
             || (state.current_context() == &JsonContext::DoubleQuoteProperty
-                && state.current_char().map_or(false, |c| c.is_alphabetic()))
+                && state.current_char().is_some_and(|c| c.is_alphabetic()))
     }
 
     fn repair(&self, state: &mut ParseState, _error: &str) -> Result<(), FuzzyJsonError> {
-        println!("Repairing missing quotes");
         state.output.push(
             if state.current_context() == &JsonContext::SingleQuoteProperty {
                 '\''
@@ -651,9 +1295,7 @@ impl RepairStrategy for CodeBlockMarkersStrategy {
     }
 
     fn repair(&self, state: &mut ParseState, _error: &str) -> Result<(), FuzzyJsonError> {
-        if state.remaining().starts_with("json```") {
-            state.advance(7);
-        } else if state.remaining().starts_with("```json") {
+        if state.remaining().starts_with("json```") || state.remaining().starts_with("```json") {
             state.advance(7);
         } else if state.remaining().starts_with("```") {
             state.advance(3);
@@ -663,26 +1305,57 @@ impl RepairStrategy for CodeBlockMarkersStrategy {
 }
 
 #[derive(Debug)]
-pub struct TrimStrayContentInBeginningStrategy;
+pub struct TrimStrayContentInBeginningStrategy {
+    /// Whether a Python tuple, `(1, 2)`, may start the document, see
+    /// `ParserOptions::allow_python_literals`.
+    pub allow_tuples: bool,
+}
+
+impl TrimStrayContentInBeginningStrategy {
+    /// Whether the document starts at the current position: an object, an array, or a tuple
+    /// opening on something that looks like a value, unlike the `(` in `Sure (see below):`.
+    fn starts_document(&self, state: &ParseState) -> bool {
+        match state.current_char() {
+            Some('{' | '[') => true,
+            Some('(') if self.allow_tuples => {
+                let first = state.remaining()[1..].trim_start();
+                first.starts_with(|c: char| {
+                    c.is_ascii_digit()
+                        || matches!(c, '-' | '+' | '.' | '"' | '\'' | '(' | ')' | '[' | '{')
+                }) || ["True", "False", "None"]
+                    .iter()
+                    .any(|literal| first.starts_with(literal))
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the document hasn't started and the input ends in a `(`, which more streamed
+    /// input would tell apart from a tuple, see `starts_document`.
+    pub(crate) fn tuple_cut_off(state: &ParseState) -> bool {
+        state.current_context() == &JsonContext::Root
+            && state.output.is_empty()
+            && state.remaining().trim_end().ends_with('(')
+    }
+}
 
 impl RepairStrategy for TrimStrayContentInBeginningStrategy {
     fn name(&self) -> &'static str {
-        "trim_stray_characters_in_end_markers"
+        "trim_stray_content_in_beginning"
     }
     fn priority(&self) -> u8 {
         70
     }
 
+    // nothing of the document has been emitted yet, so whatever precedes it is not data
     fn can_repair(&self, state: &ParseState, _error: &str) -> bool {
         state.current_context() == &JsonContext::Root
-            && (state.current_char() != Some('{') || state.current_char() != Some('['))
+            && state.output.is_empty()
+            && !self.starts_document(state)
     }
 
     fn repair(&self, state: &mut ParseState, _error: &str) -> Result<(), FuzzyJsonError> {
-        while state.current_char().is_some()
-            && state.current_char() != Some('{')
-            && state.current_char() != Some('[')
-        {
+        while state.current_char().is_some() && !self.starts_document(state) {
             // this normally works for stray chars in end as well
             // but there could be stray `{` in the end as well // those will be captured/corrected
             // by the in the end strategy
@@ -697,27 +1370,52 @@ pub struct TrimStrayContentInEndStrategy;
 
 impl RepairStrategy for TrimStrayContentInEndStrategy {
     fn name(&self) -> &'static str {
-        "trim_stray_characters_in_end_markers"
+        "trim_stray_content_in_end"
     }
     fn priority(&self) -> u8 {
         70
     }
 
     fn can_repair(&self, state: &ParseState, _error: &str) -> bool {
-        state.current_context() == &JsonContext::Root
+        state.current_context() == &JsonContext::Root && !state.output.is_empty()
         //  && (state.current_char() != Some(']') || state.current_char() != Some('}'))
     }
 
+    // trailing text may well be another document
+    fn is_lossy(&self, state: &ParseState, _error: &str) -> bool {
+        !state.remaining().trim().is_empty()
+    }
+
     fn repair(&self, state: &mut ParseState, _error: &str) -> Result<(), FuzzyJsonError> {
-        println!("Char: {:?}", state.current_char());
-        while state.current_char() != None {
-            state.advance(1);
-            println!("Char: {:?}", state.current_char());
+        if let Some(start) = Self::next_document(state) {
+            state.position = start;
+            return Err(FuzzyJsonError::AmbiguousDocuments {
+                location: state.error_location(),
+            });
         }
+        state.position = state.input.len();
         Ok(())
     }
 }
 
+impl TrimStrayContentInEndStrategy {
+    /// Where a second document starts in the trailing text, as in
+    /// `{"a": 1} Hope it helps! {"b": 2}`. With prose in between, only valid JSON of the same
+    /// kind as the first document counts: brackets in the prose, like the `[1]` citing a
+    /// source after an object, aren't one.
+    fn next_document(state: &ParseState) -> Option<usize> {
+        let open = state.output.trim_start().chars().next()?;
+        let rest = state.remaining();
+        rest.match_indices(open)
+            .find(|(at, _)| {
+                let mut documents = serde_json::Deserializer::from_str(&rest[*at..])
+                    .into_iter::<serde::de::IgnoredAny>();
+                matches!(documents.next(), Some(Ok(_)))
+            })
+            .map(|(at, _)| state.position + at)
+    }
+}
+
 #[derive(Debug)]
 pub struct SingleQuotesStrategy;
 
@@ -745,7 +1443,7 @@ impl RepairStrategy for SingleQuotesStrategy {
             if ch == '"' {
                 state.output.push('\\');
             }
-            state.output.push(ch);
+            push_string_char(&mut state.output, ch);
             state.advance(1);
         }
         if state.current_context() == &JsonContext::Colon {
@@ -761,17 +1459,82 @@ impl RepairStrategy for SingleQuotesStrategy {
     }
 }
 
-// High-priority strategy for handling LLM truncation
+/// Fixes the escape sequence a string stopped at, see `valid_escape_len`: backslashes that
+/// don't start an escape are doubled (`"C:\path"`), `\x41` and `\'` are translated,
+/// truncated `\u` escapes are padded, or dropped when the input ends in them, and unpaired
+/// surrogates become U+FFFD.
 #[derive(Debug)]
-pub struct TruncationRepairStrategy;
+pub struct InvalidEscapeStrategy;
+
+impl InvalidEscapeStrategy {
+    /// How many bytes of the invalid escape at the start of `escape` to replace, what with,
+    /// and whether that loses or invents anything.
+    fn fix(escape: &str) -> (usize, String, bool) {
+        let hex_digits = |from: usize, max: usize| {
+            escape[from..]
+                .bytes()
+                .take(max)
+                .take_while(u8::is_ascii_hexdigit)
+                .count()
+        };
+        match escape[1..].chars().next() {
+            // the input ends in a lone backslash
+            None => (1, String::new(), true),
+            Some('\'') => (2, "'".to_string(), false),
+            Some('x') if hex_digits(2, 2) == 2 => (4, format!("\\u00{}", &escape[2..4]), false),
+            Some('u') => match hex_digits(2, 4) {
+                4 => (6, "\\ufffd".to_string(), true),
+                digits if 2 + digits == escape.len() => (escape.len(), String::new(), true),
+                0 => (1, "\\\\".to_string(), false),
+                digits => (
+                    2 + digits,
+                    format!("\\u{:0>4}", &escape[2..2 + digits]),
+                    true,
+                ),
+            },
+            Some(_) => (1, "\\\\".to_string(), false),
+        }
+    }
+}
 
-impl RepairStrategy for TruncationRepairStrategy {
+impl RepairStrategy for InvalidEscapeStrategy {
     fn name(&self) -> &'static str {
-        "truncation_repair"
+        "invalid_escape"
     }
     fn priority(&self) -> u8 {
-        95
-    } // Highest priority
+        96 // only fires inside strings, ahead of truncation closing them
+    }
+
+    fn can_repair(&self, state: &ParseState, _error: &str) -> bool {
+        state.open_string.is_some()
+            && state.current_char() == Some('\\')
+            && valid_escape_len(state.remaining()).is_none()
+    }
+
+    fn repair(&self, state: &mut ParseState, _error: &str) -> Result<(), FuzzyJsonError> {
+        let (len, replacement, _) = Self::fix(state.remaining());
+        state.output.push_str(&replacement);
+        state.position += len;
+        Ok(())
+    }
+
+    // truncated `\u` escapes and lone surrogates lose the character they stood for
+    fn is_lossy(&self, state: &ParseState, _error: &str) -> bool {
+        Self::fix(state.remaining()).2
+    }
+}
+
+// High-priority strategy for handling LLM truncation
+#[derive(Debug)]
+pub struct TruncationRepairStrategy;
+
+impl RepairStrategy for TruncationRepairStrategy {
+    fn name(&self) -> &'static str {
+        "truncation_repair"
+    }
+    fn priority(&self) -> u8 {
+        95
+    } // Highest priority
 
     fn can_repair(&self, state: &ParseState, error: &str) -> bool {
         // Detect if we're at the end of input with unclosed scopes
@@ -785,6 +1548,14 @@ impl RepairStrategy for TruncationRepairStrategy {
         // Close all remaining scopes aggressively
         self.close_all_scopes(state)
     }
+
+    // closing brackets and strings is fine, but a dangling key or colon gets an invented `0`
+    fn is_lossy(&self, state: &ParseState, _error: &str) -> bool {
+        state
+            .stack
+            .iter()
+            .any(|context| context.is_key() || context == &JsonContext::Colon)
+    }
 }
 
 impl TruncationRepairStrategy {
@@ -798,15 +1569,8 @@ impl TruncationRepairStrategy {
                 JsonContext::Object => needs_closing.push('}'),
                 JsonContext::Array => needs_closing.push(']'),
                 JsonContext::DoubleQuoteProperty |JsonContext::SingleQuoteProperty => {
-                    // We might be in the middle of a property name or value
-                    //
-                    if state.output.chars().last() != Some('"')
-                        && state.output.matches('"').count() % 2 != 0
-                    {
-                        println!("maybe the root cause @ 805");
-                        needs_closing.push('"'); // Close unclosed string
-                    }
-                    // needs_closing.push('"'); // Close any unclosed string
+                    // We might be in the middle of a property name or value,
+                    // an unclosed name is closed by the special case below
                     needs_closing.push(':'); // set 0/empty
                     needs_closing.push('0'); // set 0/empty
                     // needs_closing.push('}'); // Close the object
@@ -814,29 +1578,17 @@ impl TruncationRepairStrategy {
                 JsonContext::Colon => {
                     needs_closing.push('0'); // set 0/empty
                 }
-                JsonContext::DoubleQuoteValue => {
-                    // We might be in the middle of a value
-                    if state.output.chars().last() == Some('"')
-                        && state.output.matches('"').count() % 2 != 0
-                    {
-                        needs_closing.push('"'); // Close unclosed string
-                    }
-                }
                 _ => {} // Root context doesn't need closing
             }
         }
 
         // Special case: if we're in the middle of a string
-        if self.is_in_unclosed_string(&state.output) {
-            // we can make this one redudant [todo:]
+        if state.path_tracker.in_string(&state.output) {
             needs_closing.insert(0, '"');
         }
 
         // Remove trailing comma if present
-        if state.output.trim_end().ends_with(',') {
-            let trimmed = state.output.trim_end();
-            state.output = trimmed[..trimmed.len() - 1].to_string();
-        }
+        drop_dangling_comma(state);
 
         // Apply all closings
         for &closing_char in &needs_closing {
@@ -845,31 +1597,6 @@ impl TruncationRepairStrategy {
 
         Ok(())
     }
-
-    fn is_in_unclosed_string(&self, output: &str) -> bool {
-        let mut in_string = false;
-        let mut escape_next = false;
-        let mut quote_char = '"';
-
-        for ch in output.chars() {
-            if escape_next {
-                escape_next = false;
-                continue;
-            }
-
-            match ch {
-                '\\' if in_string => escape_next = true,
-                '"' | '\'' if !in_string => {
-                    in_string = true;
-                    quote_char = ch;
-                }
-                c if in_string && c == quote_char => in_string = false,
-                _ => {}
-            }
-        }
-
-        in_string
-    }
 }
 
 // Strategy for detecting and fixing incomplete property assignments
@@ -891,6 +1618,11 @@ impl RepairStrategy for IncompletePropertyStrategy {
             || (output.ends_with('"') && state.remaining().trim().starts_with(':'))
     }
 
+    // the missing value is always filled in with an invented `null`
+    fn is_lossy(&self, _state: &ParseState, _error: &str) -> bool {
+        true
+    }
+
     fn repair(&self, state: &mut ParseState, _error: &str) -> Result<(), FuzzyJsonError> {
         let output = state.output.trim_end();
 
@@ -937,10 +1669,7 @@ impl RepairStrategy for IncompleteArrayStrategy {
 
     fn repair(&self, state: &mut ParseState, _error: &str) -> Result<(), FuzzyJsonError> {
         // Remove trailing comma and close array
-        let trimmed = state.output.trim_end();
-        if trimmed.ends_with(',') {
-            state.output = trimmed[..trimmed.len() - 1].to_string();
-        }
+        drop_dangling_comma(state);
         state.output.push(']');
         Ok(())
     }
@@ -952,12 +1681,12 @@ pub struct WhitespaceHandler;
 
 impl StateHandler for WhitespaceHandler {
     fn can_handle(&self, state: &ParseState) -> bool {
-        state.current_char().map_or(false, |c| c.is_whitespace())
+        state.current_char().is_some_and(|c| c.is_whitespace())
             || state.remaining().starts_with("\\n")
     }
 
     fn handle(&self, state: &mut ParseState) -> Result<bool, FuzzyJsonError> {
-        while state.current_char().map_or(false, |a| a.is_whitespace())
+        while state.current_char().is_some_and(|a| a.is_whitespace())
             || state.remaining().starts_with("\\n")
         {
             // state.output.push(ch);
@@ -970,8 +1699,71 @@ impl StateHandler for WhitespaceHandler {
         Ok(true)
     }
 }
+/// Skips `// line`, `/* block */` and `# shell-style` comments between tokens.
+///
+/// Strings are consumed whole by `StringHandler`, so a comment marker is only ever seen here
+/// when it sits between tokens. Only registered when `ParserOptions::allow_comments` is set.
+#[derive(Debug)]
+pub struct CommentHandler;
+
+impl CommentHandler {
+    /// Whether `text` starts with a comment.
+    pub(crate) fn starts_comment(text: &str) -> bool {
+        text.starts_with("//") || text.starts_with("/*") || text.starts_with('#')
+    }
+
+    /// Length of the comment `text` starts with, and whether it ends before the input does.
+    pub(crate) fn comment_len(text: &str) -> (usize, bool) {
+        if let Some(block) = text.strip_prefix("/*") {
+            // an unterminated block comment runs to the end of the (truncated) input
+            return block
+                .find("*/")
+                .map_or((text.len(), false), |end| (end + 4, true));
+        }
+        // line comments end at a line terminator (`\r` on its own and the U+2028 and U+2029
+        // separators JSON5 allows included) or at the escaped `\n` that the whitespace
+        // handler also treats as one
+        let line = text
+            .find(['\n', '\r', '\u{2028}', '\u{2029}'])
+            .map_or(text, |end| &text[..end]);
+        match line.find("\\n") {
+            Some(end) => (end, true),
+            None => (line.len(), line.len() < text.len()),
+        }
+    }
+}
+
+impl StateHandler for CommentHandler {
+    fn can_handle(&self, state: &ParseState) -> bool {
+        Self::starts_comment(state.remaining())
+    }
+
+    fn handle(&self, state: &mut ParseState) -> Result<bool, FuzzyJsonError> {
+        let (comment_len, _) = Self::comment_len(state.remaining());
+        state.position += comment_len;
+        Ok(true)
+    }
+}
+
 #[derive(Debug)]
-pub struct CommaHandler;
+pub struct CommaHandler {
+    /// See `ObjectHandler::allow_trailing_commas`.
+    pub allow_trailing_commas: bool,
+}
+
+impl CommaHandler {
+    /// Whether the comma at the current position comes right before a closing bracket.
+    fn trails(state: &ParseState) -> bool {
+        let mut rest = &state.remaining()[1..];
+        loop {
+            let trimmed = rest.trim_start();
+            match trimmed.strip_prefix("\\n") {
+                Some(after) => rest = after,
+                None => return trimmed.starts_with(['}', ']']),
+            }
+        }
+    }
+}
 
 impl StateHandler for CommaHandler {
     fn can_handle(&self, state: &ParseState) -> bool {
@@ -986,6 +1778,12 @@ impl StateHandler for CommaHandler {
     }
 
     fn handle(&self, state: &mut ParseState) -> Result<bool, FuzzyJsonError> {
+        if !self.allow_trailing_commas && Self::trails(state) {
+            return Err(FuzzyJsonError::UnexpectedCharacter {
+                found: ',',
+                location: state.error_location(),
+            });
+        }
         let remaining = state.remaining();
 
         if remaining.starts_with(",") {
@@ -1014,7 +1812,7 @@ impl StateHandler for CommaHandler {
                 state.pop_context();
                 return Ok(true);
             }
-            state.output.push_str(",");
+            state.output.push(',');
         }
 
         Ok(true)
@@ -1045,7 +1843,7 @@ impl StateHandler for ColonHandler {
             state.output.push(':');
             state.advance(1);
         }
-        while state.current_char().map_or(false, |a| a.is_whitespace())
+        while state.current_char().is_some_and(|a| a.is_whitespace())
             || state.remaining().starts_with("\\n")
         {
             if state.remaining().starts_with("\\n") {
@@ -1070,35 +1868,38 @@ impl StateHandler for ColonHandler {
 #[derive(Debug)]
 pub struct LiteralHandler;
 
+impl LiteralHandler {
+    const LITERALS: [(&'static str, &'static str); 4] = [
+        ("true", "true"),
+        ("false", "false"),
+        ("null", "null"),
+        ("undefined", "null"),
+    ];
+
+    /// The literal at the current position and the JSON it stands for, unless it only starts
+    /// a longer word such as the bareword `nullable`.
+    fn literal(state: &ParseState) -> Option<(&'static str, &'static str)> {
+        let remaining = state.remaining();
+        Self::LITERALS.into_iter().find(|(literal, _)| {
+            remaining.starts_with(literal)
+                && !remaining[literal.len()..].starts_with(is_identifier_part)
+        })
+    }
+}
+
 impl StateHandler for LiteralHandler {
     fn can_handle(&self, state: &ParseState) -> bool {
-        let remaining = state.remaining().trim();
         // println!("\n \n Remaining at literal handler check: {} | Context: {:?}", remaining, state.current_context());
         (state.current_context() == &JsonContext::Array
             || state.current_context() == &JsonContext::Colon
             || state.current_context().is_key())
-            && (remaining.starts_with("true")
-                || remaining.starts_with("false")
-                || remaining.starts_with("null")
-                || remaining.starts_with("undefined"))
+            && Self::literal(state).is_some()
     }
 
     fn handle(&self, state: &mut ParseState) -> Result<bool, FuzzyJsonError> {
-        let remaining = state.remaining();
-
-        if remaining.starts_with("true") {
-            state.output.push_str("true");
-            state.advance(4);
-        } else if remaining.starts_with("false") {
-            state.output.push_str("false");
-            state.advance(5);
-        } else if remaining.starts_with("null") {
-            state.output.push_str("null");
-            state.advance(4);
-        }
-        else if remaining.starts_with("undefined") {
-            state.output.push_str("null");
-            state.advance(9);
+        if let Some((literal, json)) = Self::literal(state) {
+            state.output.push_str(json);
+            state.advance(literal.len());
         }
         if state.current_context() != &JsonContext::Array {
             state.pop_context(); // if not array it would be a property or colon // what about
@@ -1109,22 +1910,46 @@ impl StateHandler for LiteralHandler {
     }
 }
 
-const VALID_KEY_FIRST_CHARS: [char; 27] = [
-    'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 's',
-    't', 'u', 'v', 'w', 'x', 'y', 'z', '_',
-];
-const VALID_KEY_REST_OF_CHARS: [char; 10] = ['1', '2', '3', '4', '5', '6', '7', '8', '9', '0'];
+/// Whether `ch` can start an ECMAScript IdentifierName, as unquoted keys are written in
+/// JavaScript object literals.
+fn is_identifier_start(ch: char) -> bool {
+    ch == '$' || ch == '_' || unicode_ident::is_xid_start(ch)
+}
 
-#[derive(Debug)]
-pub struct NoQuotesKeyHandler;
+/// Whether `ch` can continue an ECMAScript IdentifierName (ZWNJ and ZWJ included).
+fn is_identifier_part(ch: char) -> bool {
+    ch == '$' || ch == '\u{200c}' || ch == '\u{200d}' || unicode_ident::is_xid_continue(ch)
+}
+
+/// Whether `text` starts with an unquoted key and its colon, as in `{"a": "x" b: "y"}`.
+fn starts_unquoted_key(text: &str) -> bool {
+    let key_len = text
+        .find(|c: char| !is_identifier_part(c))
+        .unwrap_or(text.len());
+    text.starts_with(is_identifier_start) && text[key_len..].trim_start().starts_with(':')
+}
+
+/// Unquoted object keys (`{$id: 1, 名前: 2}`), registered when
+/// `ParserOptions::allow_unquoted_keys` is set. A key is an ECMAScript IdentifierName that
+/// may also contain `extra_chars`, e.g. the `-` and `.` of `kebab-key` and `a.b`.
+#[derive(Debug, Default)]
+pub struct NoQuotesKeyHandler {
+    pub extra_chars: String,
+}
+
+impl NoQuotesKeyHandler {
+    fn is_key_char(&self, ch: char) -> bool {
+        is_identifier_part(ch) || self.extra_chars.contains(ch)
+    }
+}
 
 impl StateHandler for NoQuotesKeyHandler {
     fn can_handle(&self, state: &ParseState) -> bool {
         (state.current_context() == &JsonContext::Object)
-            && (state
+            && !MissingCommaStrategy::comma_missing(state)
+            && state
                 .current_char()
-                .map(|c| VALID_KEY_FIRST_CHARS.contains(&c.to_ascii_lowercase()))
-                == Some(true))
+                .is_some_and(|c| is_identifier_start(c) || self.extra_chars.contains(c))
     }
 
     fn handle(&self, state: &mut ParseState) -> Result<bool, FuzzyJsonError> {
@@ -1138,9 +1963,7 @@ impl StateHandler for NoQuotesKeyHandler {
         state.output.push('"');
 
         while let Some(ch) = state.current_char() {
-            if VALID_KEY_FIRST_CHARS.contains(&ch.to_ascii_lowercase())
-                || VALID_KEY_REST_OF_CHARS.contains(&ch)
-            {
+            if self.is_key_char(ch) {
                 state.output.push(ch);
                 state.advance(1);
             } else {
@@ -1153,15 +1976,79 @@ impl StateHandler for NoQuotesKeyHandler {
     }
 }
 
+/// Unquoted string values (`{status: active}`), registered when
+/// `ParserOptions::allow_bareword_values` is set. The value runs up to the next `,`, `}`,
+/// `]` or line break, so it may contain spaces (`{status: in progress}`), or to a comment
+/// after a space when comments are allowed.
 #[derive(Debug)]
-pub struct StringHandler;
+pub struct BarewordValueHandler {
+    /// Whether a comment ends the value, see `ParserOptions::allow_comments`.
+    pub allow_comments: bool,
+}
+
+impl StateHandler for BarewordValueHandler {
+    fn can_handle(&self, state: &ParseState) -> bool {
+        (state.current_context() == &JsonContext::Colon
+            || state.current_context() == &JsonContext::Array)
+            && state.current_char().is_some_and(is_identifier_start)
+            // `NaN` and `Infinity` are numbers, see `NonFiniteNumberStrategy`
+            && NonFiniteNumberStrategy::literal(state).is_none()
+    }
+
+    fn handle(&self, state: &mut ParseState) -> Result<bool, FuzzyJsonError> {
+        let remaining = state.remaining();
+        let len = remaining
+            .find([',', '}', ']', '\n'])
+            .unwrap_or(remaining.len());
+        let mut value = &remaining[..len];
+        // `{s: active // note}`, but not the `//` in `http://x` or the `#` in `C#`
+        if self.allow_comments
+            && let Some((start, _)) = value.char_indices().find(|&(at, ch)| {
+                ch.is_whitespace() && CommentHandler::starts_comment(&value[at + ch.len_utf8()..])
+            })
+        {
+            value = &value[..start];
+        }
+        let value = value.trim_end().to_string();
+
+        state.output.push('"');
+        for ch in value.chars() {
+            match ch {
+                '"' => state.output.push_str("\\\""),
+                '\\' => state.output.push_str("\\\\"),
+                ch => push_string_char(&mut state.output, ch),
+            }
+        }
+        state.output.push('"');
+        state.position += value.len();
+
+        if state.current_context() != &JsonContext::Array {
+            state.pop_context();
+        }
+        Ok(true)
+    }
+}
+
+#[derive(Debug)]
+pub struct StringHandler {
+    /// Whether a comment after a quote shows the string ended there, see
+    /// `ParserOptions::allow_comments`.
+    pub allow_comments: bool,
+    /// Whether `'` and `‘` open strings, see `ParserOptions::allow_single_quotes`.
+    pub allow_single_quotes: bool,
+}
 
 impl StateHandler for StringHandler {
     fn can_handle(&self, state: &ParseState) -> bool {
         (state.is_sq_key_or_value() && state.current_char() == Some('\''))
             || (state.is_dq_key_or_value() && state.current_char() == Some('"'))
             || (!state.is_key_or_value()
-                && (state.current_char() == Some('"') || state.current_char() == Some('\'')))
+                && !MissingCommaStrategy::comma_missing(state)
+                && state.current_char().is_some_and(|ch| match ch {
+                    '"' | '“' | '”' => true,
+                    '\'' | '‘' | '’' => self.allow_single_quotes,
+                    _ => false,
+                }))
     }
 
     fn handle(&self, state: &mut ParseState) -> Result<bool, FuzzyJsonError> {
@@ -1173,19 +2060,35 @@ impl StateHandler for StringHandler {
             state.remaining(),
             state.output
         );*/
+        if let Some(boundary_char) = state.open_string.take() {
+            // resuming a string that the previous chunk of a stream cut off
+            self.consume_string(state, boundary_char)?;
+            return Ok(true);
+        }
+
         let boundary_char = state.current_char().unwrap(); // because this would be
         // called only if there
         // exists a current char
 
         state.output.push('"');
         state.advance(1);
+        Self::enter_string_context(state, boundary_char);
 
+        self.consume_string(state, boundary_char)?;
+        Ok(true)
+    }
+}
+
+impl StringHandler {
+    /// Pushes the context of a string that was just opened: a value after a colon or in an
+    /// array, otherwise a property name.
+    pub(crate) fn enter_string_context(state: &mut ParseState, boundary_char: char) {
         if state.current_context() == &JsonContext::Colon {
             state.pop_context();
-            state.push_context(if boundary_char == '"' {
-                JsonContext::DoubleQuoteValue
-            } else {
+            state.push_context(if Self::is_single_quote(boundary_char) {
                 JsonContext::SingleQuoteValue
+            } else {
+                JsonContext::DoubleQuoteValue
             });
         } else if state.is_prop() {
             // what if the colon is already there in json and it could be the next char itself
@@ -1205,21 +2108,52 @@ impl StateHandler for StringHandler {
                 JsonContext::SingleQuoteValue
             });*/
         } else if state.current_context() == &JsonContext::Array {
-            state.push_context(if boundary_char == '"' {
-                JsonContext::DoubleQuoteValue
-            } else {
+            state.push_context(if Self::is_single_quote(boundary_char) {
                 JsonContext::SingleQuoteValue
+            } else {
+                JsonContext::DoubleQuoteValue
             });
         } else {
-            state.push_context(if boundary_char == '"' {
-                JsonContext::DoubleQuoteProperty
-            } else {
+            state.push_context(if Self::is_single_quote(boundary_char) {
                 JsonContext::SingleQuoteProperty
+            } else {
+                JsonContext::DoubleQuoteProperty
             });
         }
+    }
+
+    /// Typographic quotes from word processors and chat UIs delimit strings like `'` and `"`.
+    fn is_single_quote(quote: char) -> bool {
+        matches!(quote, '\'' | '‘' | '’')
+    }
 
+    fn closing_quote(quote: char) -> char {
+        match quote {
+            '“' => '”',
+            '‘' => '’',
+            quote => quote,
+        }
+    }
+
+    /// Copies the string body up to and including its closing delimiter, or records the string
+    /// as still open when the input runs out first. A quote the input stops too soon after to
+    /// tell whether it closes the string is left for the next step, see `awaits_lookahead`.
+    fn consume_string(
+        &self,
+        state: &mut ParseState,
+        boundary_char: char,
+    ) -> Result<(), FuzzyJsonError> {
+        let closing_quote = Self::closing_quote(boundary_char);
+        let start = state.position;
+        let mut closed = false;
         while let Some(ch) = state.current_char() {
-            if ch == boundary_char {
+            if ch == closing_quote && state.position > start && self.lookahead_cut_off(state) {
+                break;
+            }
+            // a quote that doesn't end the string is one the LLM forgot to escape,
+            // e.g. the inner ones in `"He said "hi" to me"`
+            if ch == closing_quote && self.closes_string(state) {
+                closed = true;
                 state.output.push('"');
                 state.advance(1);
                 /*
@@ -1235,34 +2169,160 @@ impl StateHandler for StringHandler {
                 break;
             }
 
-            if ch == '\\' {
-                state.output.push('\\');
+            if ch == '"' {
+                state.output.push_str("\\\"");
                 state.advance(1);
-                if let Some(escaped) = state.current_char() {
-                    state.output.push(escaped);
-                    state.advance(1);
-                }
+            } else if ch == '\\' {
+                let Some(len) = valid_escape_len(state.remaining()) else {
+                    // left to `InvalidEscapeStrategy`, the string carries on after it
+                    state.open_string = Some(boundary_char);
+                    return Err(FuzzyJsonError::InvalidEscape {
+                        location: state.error_location(),
+                    });
+                };
+                let escape = &state.input[state.position..state.position + len];
+                state.output.push_str(escape);
+                state.position += len;
             } else {
-                state.output.push(ch);
+                push_string_char(&mut state.output, ch);
                 state.advance(1);
             }
         }
+        if !closed {
+            state.open_string = Some(boundary_char);
+        }
+        Ok(())
+    }
 
-        Ok(true)
+    /// Whether the quote at the current position really ends the string, judging by what
+    /// follows it: a delimiter (`,` `}` `]` `:`), the end of the input, a line break (the
+    /// next line starting a new key or element), or a space and the next string or
+    /// `key:`, the comma between them left out. A comment, when comments are allowed, only
+    /// counts if it doesn't hold a quote followed by a delimiter, the string's real end, so
+    /// the `#` in `"say "#hi" now"}` stays part of the text. Anything else means the quote
+    /// is part of the text.
+    pub(crate) fn closes_string(&self, state: &ParseState) -> bool {
+        let quote = state.current_char().unwrap_or('"');
+        self.closes_before(Self::after_quote(state), quote)
+    }
+
+    /// Whether `after`, the input following a closing `quote`, shows that the string ended
+    /// there, see `closes_string`.
+    fn closes_before(&self, after: &str, quote: char) -> bool {
+        let (next, spaced, line_break) = Self::skip_whitespace(after);
+        match next.chars().next() {
+            None | Some(',' | '}' | ']' | ':') => true,
+            Some('"' | '\'' | '“' | '‘') if spaced => true,
+            Some(_) if spaced && starts_unquoted_key(next) => true,
+            Some(_) if self.allow_comments && CommentHandler::starts_comment(next) => {
+                let (len, _) = CommentHandler::comment_len(next);
+                let (comment, rest) = next.split_at(len);
+                // in `"say "#hi" now"}` the comment would take the string's real end with it
+                let takes_end = comment.match_indices(quote).any(|(at, _)| {
+                    let after = comment[at + quote.len_utf8()..].trim_start();
+                    after.starts_with([',', '}', ']', ':']) || (after.is_empty() && rest.is_empty())
+                });
+                line_break || (!takes_end && self.closes_before(rest, quote))
+            }
+            Some(_) => line_break,
+        }
+    }
+
+    /// Whether the input stops before `closes_string` can tell if the quote at the current
+    /// position ends the string, which more streamed input may still change.
+    pub(crate) fn lookahead_cut_off(&self, state: &ParseState) -> bool {
+        self.cut_off_before(Self::after_quote(state))
+    }
+
+    /// Whether `after`, the input following a quote, stops before `closes_before` can tell.
+    fn cut_off_before(&self, after: &str) -> bool {
+        let (next, spaced, line_break) = Self::skip_whitespace(after);
+        if line_break {
+            return false;
+        }
+        if self.allow_comments && CommentHandler::starts_comment(next) {
+            let (len, ended) = CommentHandler::comment_len(next);
+            return !ended || self.cut_off_before(&next[len..]);
+        }
+        // a key still missing its colon
+        let word = next.trim_end();
+        let key = word.starts_with(is_identifier_start)
+            && word.trim_end_matches(is_identifier_part).is_empty();
+        next.is_empty() || next == "\\" || (self.allow_comments && next == "/") || (spaced && key)
+    }
+
+    /// Whether a streamed string stopped at a quote to wait for what follows it, see
+    /// `consume_string`.
+    pub(crate) fn awaits_lookahead(&self, state: &ParseState) -> bool {
+        if let Some(literal) = state.python_string {
+            return literal.awaits_lookahead(self, state);
+        }
+        state.open_string.is_some_and(|quote| {
+            state.current_char() == Some(Self::closing_quote(quote))
+                && self.lookahead_cut_off(state)
+        })
+    }
+
+    /// The input following the quote at the current position.
+    fn after_quote(state: &ParseState) -> &str {
+        let quote_len = state.current_char().map_or(0, char::len_utf8);
+        &state.remaining()[quote_len..]
+    }
+
+    /// What follows the whitespace at the start of `after`, skipped the way
+    /// `WhitespaceHandler` does including a literal `\n`, whether there was any, and whether
+    /// it held a line break.
+    fn skip_whitespace(after: &str) -> (&str, bool, bool) {
+        let mut next = after;
+        let mut line_break = false;
+        loop {
+            let trimmed = next.trim_start();
+            line_break |= next[..next.len() - trimmed.len()].contains('\n');
+            match trimmed.strip_prefix("\\n") {
+                Some(rest) => {
+                    line_break = true;
+                    next = rest;
+                }
+                None => {
+                    next = trimmed;
+                    break;
+                }
+            }
+        }
+        (next, next.len() < after.len(), line_break)
     }
 }
 
+/// Numbers in objects and arrays, written the way JSON, JavaScript or Python would, see
+/// `literal_len`. A document that is only a number (`+5`, `0x1F`, `NaN`) isn't repaired: at
+/// the top level, text before the first `{` or `[` is taken for the model's preamble, as in
+/// `Step 1: {...}`, so only a top-level number that is already valid JSON is accepted.
 #[derive(Debug)]
 pub struct NumberHandler;
 
 impl StateHandler for NumberHandler {
+    // a sign without digits yet may still turn out to be `-Infinity` or be cut off, and is
+    // left to `NonFiniteNumberStrategy` and `BareSignStrategy`
     fn can_handle(&self, state: &ParseState) -> bool {
-        state
-            .current_char()
-            .map_or(false, |c| c.is_ascii_digit() || c == '-')
+        let remaining = state.remaining();
+        let unsigned = remaining.strip_prefix(['-', '+']).unwrap_or(remaining);
+        let unsigned = unsigned.strip_prefix('.').unwrap_or(unsigned);
+        unsigned.starts_with(|c: char| c.is_ascii_digit())
     }
 
     fn handle(&self, state: &mut ParseState) -> Result<bool, FuzzyJsonError> {
+        // `1.5.3` may be a version or a typo, there's no telling which number was meant
+        let value = matches!(
+            state.current_context(),
+            JsonContext::Colon | JsonContext::DoubleQuoteProperty | JsonContext::Array
+        );
+        let remaining = state.remaining();
+        if value && remaining[Self::literal_len(remaining)..].starts_with('.') {
+            return Err(FuzzyJsonError::InvalidNumber {
+                location: state.error_location(),
+            });
+        }
+
         if state.current_context() == &JsonContext::Colon {
             state.pop_context();
             state.push_context(JsonContext::DoubleQuoteValue);
@@ -1277,13 +2337,26 @@ impl StateHandler for NumberHandler {
             state.output.push('"');
         }
 
-        while let Some(ch) = state.current_char() {
-            if ch.is_ascii_digit() || ch == '-' || ch == '+' || ch == '.' || ch == 'e' || ch == 'E'
-            {
-                state.output.push(ch);
-                state.advance(1);
-            } else {
-                break;
+        if state.current_context() == &JsonContext::DoubleQuoteValue {
+            let len = Self::literal_len(state.remaining());
+            let literal = &state.input[state.position..state.position + len];
+            state.output.push_str(&Self::normalize(literal));
+            state.position += len;
+        } else {
+            // a numbered key is kept as written
+            while let Some(ch) = state.current_char() {
+                if ch.is_ascii_digit()
+                    || ch == '-'
+                    || ch == '+'
+                    || ch == '.'
+                    || ch == 'e'
+                    || ch == 'E'
+                {
+                    state.output.push(ch);
+                    state.advance(1);
+                } else {
+                    break;
+                }
             }
         }
 
@@ -1292,7 +2365,7 @@ impl StateHandler for NumberHandler {
         } else if state.current_context() == &JsonContext::DoubleQuoteProperty
             && state
                 .current_char()
-                .map_or(true, |c| c.is_whitespace() || c == ':' || c == '}')
+                .is_none_or(|c| c.is_whitespace() || c == ':' || c == '}')
         {
             state.output.push('"');
         }
@@ -1300,16 +2373,210 @@ impl StateHandler for NumberHandler {
     }
 }
 
+impl NumberHandler {
+    /// Length of the number literal `text` starts with, allowing for what JavaScript and
+    /// Python accept on top of JSON: a leading `+`, hex (`0x1F`), `_` separators and a
+    /// missing integer or fraction part (`.5`, `5.`).
+    fn literal_len(text: &str) -> usize {
+        let bytes = text.as_bytes();
+        let mut len = usize::from(matches!(bytes.first(), Some(b'+' | b'-')));
+        let digits_from = |from: usize, hex: bool| {
+            from + bytes[from..]
+                .iter()
+                .take_while(|b| b.is_ascii_digit() || **b == b'_' || (hex && b.is_ascii_hexdigit()))
+                .count()
+        };
+
+        if matches!(bytes.get(len..len + 2), Some(b"0x" | b"0X")) {
+            return digits_from(len + 2, true);
+        }
+        len = digits_from(len, false);
+        if bytes.get(len) == Some(&b'.') {
+            len = digits_from(len + 1, false);
+        }
+        if matches!(bytes.get(len), Some(b'e' | b'E')) {
+            len += 1;
+            if matches!(bytes.get(len), Some(b'+' | b'-')) {
+                len += 1;
+            }
+            len = digits_from(len, false);
+        }
+        len
+    }
+
+    /// Rewrites a literal matched by `literal_len` as a JSON number.
+    fn normalize(literal: &str) -> String {
+        let (sign, unsigned) = match literal.as_bytes().first() {
+            Some(b'-') => ("-", &literal[1..]),
+            Some(b'+') => ("", &literal[1..]),
+            _ => ("", literal),
+        };
+        let unsigned = unsigned.replace('_', "");
+        if !unsigned.bytes().any(|b| b.is_ascii_digit()) {
+            return literal.to_string();
+        }
+
+        if let Some(hex) = unsigned
+            .strip_prefix("0x")
+            .or_else(|| unsigned.strip_prefix("0X"))
+        {
+            let value = match u64::from_str_radix(hex, 16) {
+                Ok(value) => value.to_string(),
+                // too big for an integer, JSON numbers don't have to fit one though
+                Err(_) => hex
+                    .chars()
+                    .filter_map(|c| c.to_digit(16))
+                    .fold(0.0, |value, digit| value * 16.0 + f64::from(digit))
+                    .to_string(),
+            };
+            return format!("{sign}{value}");
+        }
+
+        let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+            Some(at) => unsigned.split_at(at),
+            None => (unsigned.as_str(), ""),
+        };
+        let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let integer = match integer.trim_start_matches('0') {
+            "" => "0",
+            integer => integer,
+        };
+        let mut number = format!("{sign}{integer}");
+        if !fraction.is_empty() {
+            number.push('.');
+            number.push_str(fraction);
+        }
+        // an exponent without digits was cut off
+        if exponent.bytes().any(|b| b.is_ascii_digit()) {
+            number.push_str(exponent);
+        }
+        number
+    }
+}
+
+/// `NaN`, `Infinity` and `-Infinity` as written by JavaScript and Python, turned into what
+/// `ParserOptions::non_finite_numbers` asks for. Turning them into `null` loses the value, so
+/// strict mode refuses that.
+#[derive(Debug)]
+pub struct NonFiniteNumberStrategy {
+    pub policy: NonFiniteNumbers,
+}
+
+impl NonFiniteNumberStrategy {
+    const LITERALS: [&'static str; 4] = ["NaN", "Infinity", "-Infinity", "+Infinity"];
+
+    fn literal(state: &ParseState) -> Option<&'static str> {
+        Self::LITERALS
+            .into_iter()
+            .find(|literal| state.remaining().starts_with(literal))
+    }
+}
+
+impl RepairStrategy for NonFiniteNumberStrategy {
+    fn name(&self) -> &'static str {
+        "non_finite_number"
+    }
+    fn priority(&self) -> u8 {
+        90
+    }
+
+    fn can_repair(&self, state: &ParseState, _error: &str) -> bool {
+        (state.current_context() == &JsonContext::Array
+            || state.current_context() == &JsonContext::Colon
+            || state.current_context().is_key())
+            && Self::literal(state).is_some()
+    }
+
+    fn is_lossy(&self, _state: &ParseState, _error: &str) -> bool {
+        self.policy == NonFiniteNumbers::Null
+    }
+
+    fn repair(&self, state: &mut ParseState, _error: &str) -> Result<(), FuzzyJsonError> {
+        if let Some(literal) = Self::literal(state) {
+            match self.policy {
+                NonFiniteNumbers::Null => state.output.push_str("null"),
+                NonFiniteNumbers::String => {
+                    let name = literal.trim_start_matches('+');
+                    state.output.push_str(&format!("\"{name}\""));
+                }
+            }
+            state.advance(literal.len());
+        }
+        if state.current_context() != &JsonContext::Array {
+            state.pop_context();
+        }
+        Ok(())
+    }
+}
+
+/// A `-` or `+` with no number after it, as in `{"a": -}`. It is dropped, and a value that
+/// was nothing else becomes `null`.
+#[derive(Debug)]
+pub struct BareSignStrategy;
+
+impl RepairStrategy for BareSignStrategy {
+    fn name(&self) -> &'static str {
+        "bare_sign"
+    }
+    fn priority(&self) -> u8 {
+        90 // ahead of incomplete property strategy filling in the value it was meant to be
+    }
+
+    fn can_repair(&self, state: &ParseState, _error: &str) -> bool {
+        (state.current_context() == &JsonContext::Array
+            || state.current_context() == &JsonContext::Colon)
+            && matches!(state.current_char(), Some('-' | '+'))
+            && !NumberHandler.can_handle(state)
+            && NonFiniteNumberStrategy::literal(state).is_none()
+            && !Self::cut_off(&state.remaining()[1..])
+    }
+
+    fn is_lossy(&self, _state: &ParseState, _error: &str) -> bool {
+        true
+    }
+
+    fn repair(&self, state: &mut ParseState, _error: &str) -> Result<(), FuzzyJsonError> {
+        state.advance(1);
+        let next = state.remaining().trim_start();
+        if next.is_empty() || next.starts_with([',', '}', ']']) {
+            state.output.push_str("null");
+            if state.current_context() == &JsonContext::Colon {
+                state.pop_context();
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BareSignStrategy {
+    /// Whether the input after the sign stops where the rest of a number or of `Infinity`
+    /// may still follow, as a streamed chunk does.
+    fn cut_off(rest: &str) -> bool {
+        !rest.is_empty() && (rest == "." || "Infinity".starts_with(rest))
+    }
+}
+
 #[derive(Debug)]
-pub struct ObjectHandler;
+pub struct ObjectHandler {
+    /// Whether a comma may come right before the `}`, see
+    /// `ParserOptions::allow_trailing_commas`.
+    pub allow_trailing_commas: bool,
+}
 
 impl StateHandler for ObjectHandler {
     fn can_handle(&self, state: &ParseState) -> bool {
         state.current_char() == Some('{')
-            || (state.current_context() != &JsonContext::Root && state.current_char() == Some('}'))
+            || (state.current_char() == Some('}')
+                && !matches!(
+                    state.current_context(),
+                    JsonContext::Root | JsonContext::Array
+                ))
     }
 
     fn handle(&self, state: &mut ParseState) -> Result<bool, FuzzyJsonError> {
+        if state.current_char() == Some('}') && !self.allow_trailing_commas {
+            reject_trailing_comma(state)?;
+        }
         if state.current_context() == &JsonContext::Colon {
             state.pop_context();
         }
@@ -1319,6 +2586,7 @@ impl StateHandler for ObjectHandler {
                 state.push_context(JsonContext::Object);
                 state.advance(1);
             } else if ch == '}' {
+                drop_dangling_comma(state);
                 state.output.push('}');
                 state.pop_context();
                 state.advance(1);
@@ -1329,14 +2597,25 @@ impl StateHandler for ObjectHandler {
 }
 
 #[derive(Debug)]
-pub struct ArrayHandler;
+pub struct ArrayHandler {
+    /// See `ObjectHandler::allow_trailing_commas`.
+    pub allow_trailing_commas: bool,
+}
 
 impl StateHandler for ArrayHandler {
     fn can_handle(&self, state: &ParseState) -> bool {
-        state.current_char() == Some('[') || state.current_char() == Some(']')
+        state.current_char() == Some('[')
+            || (state.current_char() == Some(']')
+                && !matches!(
+                    state.current_context(),
+                    JsonContext::Root | JsonContext::Object
+                ))
     }
 
     fn handle(&self, state: &mut ParseState) -> Result<bool, FuzzyJsonError> {
+        if state.current_char() == Some(']') && !self.allow_trailing_commas {
+            reject_trailing_comma(state)?;
+        }
         if state.current_context() == &JsonContext::Colon {
             state.pop_context();
         }
@@ -1346,6 +2625,7 @@ impl StateHandler for ArrayHandler {
                 state.push_context(JsonContext::Array);
                 state.advance(1);
             } else if ch == ']' {
+                drop_dangling_comma(state);
                 state.output.push(']');
                 state.pop_context();
                 state.advance(1);
@@ -1355,6 +2635,98 @@ impl StateHandler for ArrayHandler {
     }
 }
 
+/// Backtick-quoted strings, which some models write in place of `"`, registered when
+/// `ParserOptions::allow_backtick_strings` is set. Code fences are left to
+/// `CodeBlockMarkersStrategy`.
+#[derive(Debug)]
+pub struct BacktickStringHandler {
+    /// See `StringHandler::allow_comments`.
+    pub allow_comments: bool,
+}
+
+impl BacktickStringHandler {
+    /// Whether the input ends in backticks that could still become a closing "```" fence,
+    /// which more streamed input would tell apart from the start of a string.
+    pub(crate) fn fence_cut_off(state: &ParseState) -> bool {
+        let remaining = state.remaining();
+        !remaining.is_empty() && remaining.len() < 3 && "```".starts_with(remaining)
+    }
+}
+
+impl StateHandler for BacktickStringHandler {
+    fn can_handle(&self, state: &ParseState) -> bool {
+        !state.is_key_or_value()
+            && state.current_char() == Some('`')
+            && !state.remaining().starts_with("```")
+    }
+
+    fn handle(&self, state: &mut ParseState) -> Result<bool, FuzzyJsonError> {
+        let strings = StringHandler {
+            allow_comments: self.allow_comments,
+            allow_single_quotes: false,
+        };
+        strings.handle(state)
+    }
+}
+
+/// Appends one character of a string body, escaping the control characters JSON doesn't
+/// allow raw, like the real newlines and tabs in multi-line text.
+fn push_string_char(output: &mut String, ch: char) {
+    match ch {
+        '\n' => output.push_str("\\n"),
+        '\r' => output.push_str("\\r"),
+        '\t' => output.push_str("\\t"),
+        '\u{8}' => output.push_str("\\b"),
+        '\u{c}' => output.push_str("\\f"),
+        '\0'..='\u{1f}' => output.push_str(&format!("\\u{:04x}", ch as u32)),
+        _ => output.push(ch),
+    }
+}
+
+/// Length of the valid JSON escape sequence `text` starts with, `None` if the backslash it
+/// starts with doesn't begin one. A `\u` escape of a UTF-16 surrogate is only valid as a
+/// high/low pair, which counts as a single escape.
+pub(crate) fn valid_escape_len(text: &str) -> Option<usize> {
+    let code_unit = |from: usize| {
+        text.get(from..from + 4)
+            .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+            .and_then(|hex| u16::from_str_radix(hex, 16).ok())
+    };
+    match text.as_bytes().get(1)? {
+        b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't' => Some(2),
+        b'u' => match code_unit(2)? {
+            0xD800..=0xDBFF => {
+                let low = text.get(6..8).filter(|u| *u == "\\u").and(code_unit(8))?;
+                (0xDC00..=0xDFFF).contains(&low).then_some(12)
+            }
+            0xDC00..=0xDFFF => None,
+            _ => Some(6),
+        },
+        _ => None,
+    }
+}
+
+/// Removes a comma left right before a closing bracket, e.g. when a comment sat between them.
+fn drop_dangling_comma(state: &mut ParseState) {
+    if let Some(trimmed) = state.output.trim_end().strip_suffix(',') {
+        state.output.truncate(trimmed.len());
+    }
+}
+
+/// Fails on the closing bracket at the current position when a comma comes right before
+/// it, for when trailing commas aren't allowed.
+fn reject_trailing_comma(state: &ParseState) -> Result<(), FuzzyJsonError> {
+    match state.current_char() {
+        Some(found) if state.output.trim_end().ends_with(',') => {
+            Err(FuzzyJsonError::UnexpectedCharacter {
+                found,
+                location: state.error_location(),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
 // Builder pattern for easy configuration
 pub struct FuzzyJsonParserBuilder {
     options: ParserOptions,
@@ -1391,6 +2763,17 @@ impl FuzzyJsonParserBuilder {
         self
     }
 
+    /// Characters unquoted keys may contain besides those of an identifier, `-.` by default.
+    pub fn with_unquoted_key_chars(mut self, chars: impl Into<String>) -> Self {
+        self.options.unquoted_key_chars = chars.into();
+        self
+    }
+
+    pub fn with_bareword_values(mut self, allow: bool) -> Self {
+        self.options.allow_bareword_values = allow;
+        self
+    }
+
     pub fn strict_mode(mut self, strict: bool) -> Self {
         self.options.strict_mode = strict;
         self
@@ -1401,11 +2784,64 @@ impl FuzzyJsonParserBuilder {
         self
     }
 
+    /// Refuses input longer than `max` bytes, see `ParserOptions::max_input_bytes`.
+    pub fn max_input_bytes(mut self, max: usize) -> Self {
+        self.options.max_input_bytes = Some(max);
+        self
+    }
+
+    pub fn max_depth(mut self, max: usize) -> Self {
+        self.options.max_depth = Some(max);
+        self
+    }
+
+    pub fn max_string_length(mut self, max: usize) -> Self {
+        self.options.max_string_length = Some(max);
+        self
+    }
+
+    pub fn max_entries(mut self, max: usize) -> Self {
+        self.options.max_entries = Some(max);
+        self
+    }
+
+    pub fn max_steps(mut self, max: usize) -> Self {
+        self.options.max_steps = Some(max);
+        self
+    }
+
+    pub fn max_duration(mut self, max: Duration) -> Self {
+        self.options.max_duration = Some(max);
+        self
+    }
+
     pub fn aggressive_truncation_repair(mut self, enable: bool) -> Self {
         self.options.aggressive_truncation_repair = enable;
         self
     }
 
+    pub fn with_backtick_strings(mut self, allow: bool) -> Self {
+        self.options.allow_backtick_strings = allow;
+        self
+    }
+
+    pub fn with_python_literals(mut self, allow: bool) -> Self {
+        self.options.allow_python_literals = allow;
+        self
+    }
+
+    pub fn with_non_finite_numbers(mut self, policy: NonFiniteNumbers) -> Self {
+        self.options.non_finite_numbers = policy;
+        self
+    }
+
+    /// Validates every repaired value against `schema`, fixing up what can be fixed,
+    /// see `FuzzyJsonParser::parse_validated`.
+    pub fn with_schema(mut self, schema: Value) -> Self {
+        self.options.schema = Some(schema);
+        self
+    }
+
     pub fn add_strategy(mut self, strategy: Box<dyn RepairStrategy>) -> Self {
         self.custom_strategies.push(strategy);
         self
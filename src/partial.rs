@@ -0,0 +1,260 @@
+//! The value a stream has produced so far, kept up to date from the output the repair appends
+//! rather than parsed again from the start for every snapshot.
+
+use serde::de::Error as _;
+use serde_json::Value;
+
+/// Builds the value out of the repaired output of a `StreamingParser` as it settles, see
+/// `settle`, and previews it with whatever closing the open scopes would add, see `preview`.
+#[derive(Debug, Default)]
+pub(crate) struct PartialValue {
+    value: Option<Value>,
+    cursor: Cursor,
+    /// Bytes of the output fed to `settle`.
+    fed: usize,
+    /// What the last `preview` inserted, most recent last.
+    provisional: Vec<Undo>,
+}
+
+/// Where reading the output has got to.
+#[derive(Debug, Clone, Default)]
+struct Cursor {
+    /// Arrays and objects still open, outermost first.
+    frames: Vec<Frame>,
+    /// Text of a scalar whose end hasn't been seen yet, e.g. a string still missing its
+    /// closing quote.
+    token: String,
+    /// Inside the string `token` holds, and whether the last char was an escaping `\`.
+    string: Option<bool>,
+}
+
+#[derive(Debug, Clone)]
+struct Frame {
+    /// Where the container sits in its parent, `None` for the root.
+    slot: Option<Slot>,
+    kind: Kind,
+}
+
+#[derive(Debug, Clone)]
+enum Kind {
+    Array,
+    /// With the key read last, until its value comes.
+    Object(Option<String>),
+}
+
+#[derive(Debug, Clone)]
+enum Slot {
+    Key(String),
+    Index(usize),
+}
+
+/// How to take back one insertion: the path of the container it was made in, where in the
+/// container, and what was there before.
+#[derive(Debug)]
+struct Undo {
+    path: Vec<Slot>,
+    slot: Option<Slot>,
+    previous: Option<Value>,
+}
+
+impl PartialValue {
+    /// Bytes of the output read so far.
+    pub(crate) fn fed(&self) -> usize {
+        self.fed
+    }
+
+    /// Reads `output` from where the last call stopped. Everything in it must be final, as
+    /// the output up to its trailing whitespace and commas is.
+    pub(crate) fn settle(&mut self, output: &str) -> Result<(), serde_json::Error> {
+        self.undo();
+        let mut cursor = std::mem::take(&mut self.cursor);
+        let result = output[self.fed..]
+            .chars()
+            .try_for_each(|ch| self.read(&mut cursor, ch, None));
+        self.cursor = cursor;
+        self.fed = output.len();
+        result
+    }
+
+    /// What has been read so far, `None` until a value has started.
+    pub(crate) fn value(&self) -> Option<&Value> {
+        self.value.as_ref()
+    }
+
+    /// Reads `tail`, the output the settled part would be completed with, on top of it. The
+    /// tail is taken back again by the next call.
+    pub(crate) fn preview(&mut self, tail: &str) -> Result<(), serde_json::Error> {
+        self.undo();
+        let mut cursor = self.cursor.clone();
+        let mut undo = Vec::new();
+        let result = tail
+            .chars()
+            .try_for_each(|ch| self.read(&mut cursor, ch, Some(&mut undo)))
+            .and_then(|_| self.end_token(&mut cursor, Some(&mut undo)));
+        self.provisional = undo;
+        result
+    }
+
+    /// Takes back what the last `preview` inserted.
+    fn undo(&mut self) {
+        while let Some(undo) = self.provisional.pop() {
+            let Some(slot) = undo.slot else {
+                self.value = undo.previous;
+                continue;
+            };
+            let Some(container) = descend(self.value.as_mut(), &undo.path) else {
+                continue;
+            };
+            match (slot, container) {
+                (Slot::Index(index), Value::Array(items)) => items.truncate(index),
+                (Slot::Key(key), Value::Object(entries)) => match undo.previous {
+                    Some(previous) => {
+                        entries.insert(key, previous);
+                    }
+                    None => {
+                        entries.remove(&key);
+                    }
+                },
+                _ => {}
+            }
+        }
+    }
+
+    fn read(
+        &mut self,
+        cursor: &mut Cursor,
+        ch: char,
+        undo: Option<&mut Vec<Undo>>,
+    ) -> Result<(), serde_json::Error> {
+        if let Some(escaped) = cursor.string {
+            cursor.token.push(ch);
+            cursor.string = match ch {
+                _ if escaped => Some(false),
+                '\\' => Some(true),
+                '"' => None,
+                _ => Some(false),
+            };
+            if cursor.string.is_none() {
+                return self.end_token(cursor, undo);
+            }
+            return Ok(());
+        }
+
+        match ch {
+            '"' => {
+                cursor.token.push(ch);
+                cursor.string = Some(false);
+                Ok(())
+            }
+            '{' | '[' => {
+                let (container, kind) = match ch {
+                    '{' => (Value::Object(Default::default()), Kind::Object(None)),
+                    _ => (Value::Array(Vec::new()), Kind::Array),
+                };
+                let slot = self.insert(cursor, container, undo)?;
+                cursor.frames.push(Frame { slot, kind });
+                Ok(())
+            }
+            '}' | ']' => {
+                self.end_token(cursor, undo)?;
+                cursor.frames.pop();
+                Ok(())
+            }
+            ',' | ':' => self.end_token(cursor, undo),
+            ch if ch.is_whitespace() => self.end_token(cursor, undo),
+            ch => {
+                cursor.token.push(ch);
+                Ok(())
+            }
+        }
+    }
+
+    /// Places the scalar read into `cursor.token`, if there is one: as the key of an object
+    /// still waiting for one, otherwise as a value.
+    fn end_token(
+        &mut self,
+        cursor: &mut Cursor,
+        undo: Option<&mut Vec<Undo>>,
+    ) -> Result<(), serde_json::Error> {
+        if cursor.token.is_empty() || cursor.string.is_some() {
+            return Ok(());
+        }
+        let token = std::mem::take(&mut cursor.token);
+        if let Some(Frame {
+            kind: Kind::Object(key @ None),
+            ..
+        }) = cursor.frames.last_mut()
+        {
+            *key = Some(serde_json::from_str(&token)?);
+            return Ok(());
+        }
+        let value = serde_json::from_str(&token)?;
+        self.insert(cursor, value, undo).map(|_| ())
+    }
+
+    /// Adds `value` to the innermost open container, or makes it the root, returning where
+    /// it went.
+    fn insert(
+        &mut self,
+        cursor: &mut Cursor,
+        value: Value,
+        undo: Option<&mut Vec<Undo>>,
+    ) -> Result<Option<Slot>, serde_json::Error> {
+        let Some(frame) = cursor.frames.last_mut() else {
+            let previous = self.value.replace(value);
+            if let Some(undo) = undo {
+                undo.push(Undo {
+                    path: Vec::new(),
+                    slot: None,
+                    previous,
+                });
+            }
+            return Ok(None);
+        };
+        let slot_key = match &mut frame.kind {
+            Kind::Object(key) => Some(
+                key.take()
+                    .ok_or_else(|| serde_json::Error::custom("object value without a key"))?,
+            ),
+            Kind::Array => None,
+        };
+
+        let path: Vec<Slot> = cursor
+            .frames
+            .iter()
+            .filter_map(|frame| frame.slot.clone())
+            .collect();
+        let container = descend(self.value.as_mut(), &path)
+            .ok_or_else(|| serde_json::Error::custom("value outside of its container"))?;
+        let (slot, previous) = match (container, slot_key) {
+            (Value::Array(items), None) => {
+                items.push(value);
+                (Slot::Index(items.len() - 1), None)
+            }
+            (Value::Object(entries), Some(key)) => {
+                let previous = entries.insert(key.clone(), value);
+                (Slot::Key(key), previous)
+            }
+            _ => return Err(serde_json::Error::custom("value outside of its container")),
+        };
+        if let Some(undo) = undo {
+            undo.push(Undo {
+                path,
+                slot: Some(slot.clone()),
+                previous,
+            });
+        }
+        Ok(Some(slot))
+    }
+}
+
+/// The value at `path` below `value`.
+fn descend<'v>(mut value: Option<&'v mut Value>, path: &[Slot]) -> Option<&'v mut Value> {
+    for slot in path {
+        value = match slot {
+            Slot::Key(key) => value?.get_mut(key),
+            Slot::Index(index) => value?.get_mut(index),
+        };
+    }
+    value
+}
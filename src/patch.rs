@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// One RFC 6902 JSON Patch operation, serialized as `{"op": "add", "path": ..., "value": ...}`.
+///
+/// Paths are RFC 6901 JSON Pointers. Elements appended to an array use the `-` index.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+}
+
+/// Operations that turn `old` into `new`, `None` standing for a document that hasn't
+/// started yet.
+///
+/// Tuned for documents that grow: new keys and array elements become `add`s, a scalar that
+/// changed (a string or number still being written) is `replace`d, and anything a later
+/// snapshot no longer holds, such as a value invented for a dangling key, is `remove`d.
+pub fn diff(old: Option<&Value>, new: &Value) -> Vec<PatchOperation> {
+    let mut operations = Vec::new();
+    match old {
+        Some(old) => diff_values(old, new, &mut String::new(), &mut operations),
+        None => operations.push(PatchOperation::Add {
+            path: String::new(),
+            value: new.clone(),
+        }),
+    }
+    operations
+}
+
+fn diff_values(old: &Value, new: &Value, path: &mut String, operations: &mut Vec<PatchOperation>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => diff_objects(old, new, path, operations),
+        (Value::Array(old), Value::Array(new)) => diff_arrays(old, new, path, operations),
+        _ if old != new => operations.push(PatchOperation::Replace {
+            path: path.clone(),
+            value: new.clone(),
+        }),
+        _ => {}
+    }
+}
+
+fn diff_objects(
+    old: &Map<String, Value>,
+    new: &Map<String, Value>,
+    path: &mut String,
+    operations: &mut Vec<PatchOperation>,
+) {
+    let len = path.len();
+    for key in old.keys().filter(|key| !new.contains_key(*key)) {
+        push_token(path, key);
+        operations.push(PatchOperation::Remove { path: path.clone() });
+        path.truncate(len);
+    }
+    for (key, value) in new {
+        push_token(path, key);
+        match old.get(key) {
+            Some(old_value) => diff_values(old_value, value, path, operations),
+            None => operations.push(PatchOperation::Add {
+                path: path.clone(),
+                value: value.clone(),
+            }),
+        }
+        path.truncate(len);
+    }
+}
+
+fn diff_arrays(
+    old: &[Value],
+    new: &[Value],
+    path: &mut String,
+    operations: &mut Vec<PatchOperation>,
+) {
+    let len = path.len();
+    for (index, (old_value, value)) in old.iter().zip(new).enumerate() {
+        push_token(path, &index.to_string());
+        diff_values(old_value, value, path, operations);
+        path.truncate(len);
+    }
+    // remove from the back so earlier indices stay valid
+    for index in (new.len()..old.len()).rev() {
+        push_token(path, &index.to_string());
+        operations.push(PatchOperation::Remove { path: path.clone() });
+        path.truncate(len);
+    }
+    for value in new.iter().skip(old.len()) {
+        operations.push(PatchOperation::Add {
+            path: format!("{path}/-"),
+            value: value.clone(),
+        });
+    }
+}
+
+/// Appends `token` to a JSON Pointer, escaping `~` and `/` as RFC 6901 requires.
+pub(crate) fn push_token(path: &mut String, token: &str) {
+    path.push('/');
+    path.push_str(&token.replace('~', "~0").replace('/', "~1"));
+}
@@ -0,0 +1,219 @@
+//! JSON Pointer of the value being parsed, kept up to date by scanning the repaired output
+//! as it grows rather than by every handler.
+
+use crate::patch;
+
+/// Scans the output incrementally. Handlers and repairs only ever append to the output or
+/// take back trailing whitespace and commas, so everything before that tail is final and
+/// only has to be scanned once.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PathTracker {
+    scan: Scan,
+    /// Bytes of the output `scan` has seen.
+    scanned: usize,
+}
+
+impl PathTracker {
+    /// Scans the part of `output` no repair can take back any more.
+    pub(crate) fn sync(&mut self, output: &str) {
+        let kept = output
+            .trim_end_matches(|c: char| c.is_whitespace() || c == ',')
+            .len();
+        if kept < self.scanned {
+            // only happens if the output was rewritten behind our back
+            *self = Self::default();
+        }
+        self.scan.feed(&output[self.scanned..kept]);
+        self.scanned = kept;
+    }
+
+    /// Pointer to the value the output ends inside of, `output` being the one last synced
+    /// or an extension of it.
+    pub(crate) fn pointer(&self, output: &str) -> String {
+        let Some(unscanned) = output.get(self.scanned..) else {
+            let mut tracker = Self::default();
+            tracker.sync(output);
+            return tracker.pointer(output);
+        };
+        let mut scan = self.scan.clone();
+        scan.feed(unscanned);
+        scan.pointer()
+    }
+
+    /// Whether `output`, the one last synced or an extension of it, ends inside a string.
+    pub(crate) fn in_string(&self, output: &str) -> bool {
+        let Some(unscanned) = output.get(self.scanned..) else {
+            let mut tracker = Self::default();
+            tracker.sync(output);
+            return tracker.in_string(output);
+        };
+        let mut escaped = self.scan.string.as_ref().map(|string| string.escaped);
+        for ch in unscanned.chars() {
+            escaped = match (escaped, ch) {
+                (None, '"') | (Some(true), _) => Some(false),
+                (Some(false), '\\') => Some(true),
+                (Some(false), '"') | (None, _) => None,
+                (Some(false), _) => Some(false),
+            };
+        }
+        escaped.is_some()
+    }
+
+    /// Largest nesting, string and container scanned so far.
+    pub(crate) fn extremes(&self) -> &Extremes {
+        &self.scan.extremes
+    }
+}
+
+/// Largest nesting, string and container in the scanned output, each with the offset it was
+/// first reached at, for holding valid JSON to the limits in `ParserOptions`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Extremes {
+    pub(crate) depth: Peak,
+    /// In bytes as written to the output, escapes included.
+    pub(crate) string_length: Peak,
+    /// Keys of an object or elements of an array.
+    pub(crate) entries: Peak,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Peak {
+    pub(crate) value: usize,
+    pub(crate) offset: usize,
+}
+
+impl Peak {
+    fn raise(&mut self, value: usize, offset: usize) {
+        if value > self.value {
+            *self = Self { value, offset };
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Frame {
+    Object {
+        key: Option<String>,
+        after_key: bool,
+        entries: usize,
+    },
+    Array(usize),
+}
+
+#[derive(Debug, Clone, Default)]
+struct Scan {
+    frames: Vec<Frame>,
+    /// Inside a string literal; keys are collected to be decoded once they end.
+    string: Option<StringScan>,
+    /// Bytes fed so far.
+    fed: usize,
+    extremes: Extremes,
+}
+
+#[derive(Debug, Clone)]
+struct StringScan {
+    key: Option<String>,
+    escaped: bool,
+    /// Bytes of the literal so far, without the opening quote.
+    len: usize,
+}
+
+impl Scan {
+    fn feed(&mut self, text: &str) {
+        for ch in text.chars() {
+            let offset = self.fed;
+            self.fed += ch.len_utf8();
+            if let Some(string) = &mut self.string {
+                if let Some(key) = &mut string.key {
+                    key.push(ch);
+                }
+                if string.escaped || ch != '"' {
+                    string.len += ch.len_utf8();
+                    self.extremes.string_length.raise(string.len, offset);
+                }
+                if string.escaped {
+                    string.escaped = false;
+                } else if ch == '\\' {
+                    string.escaped = true;
+                } else if ch == '"' {
+                    let literal = self.string.take().and_then(|string| string.key);
+                    if let (Some(literal), Some(Frame::Object { key, .. })) =
+                        (literal, self.frames.last_mut())
+                    {
+                        *key = Some(
+                            serde_json::from_str(&literal)
+                                .unwrap_or_else(|_| literal.trim_matches('"').to_string()),
+                        );
+                    }
+                }
+                continue;
+            }
+
+            match ch {
+                '"' => {
+                    let is_key = matches!(
+                        self.frames.last(),
+                        Some(Frame::Object {
+                            after_key: false,
+                            ..
+                        })
+                    );
+                    self.string = Some(StringScan {
+                        key: is_key.then(|| "\"".to_string()),
+                        escaped: false,
+                        len: 0,
+                    });
+                }
+                '{' | '[' => {
+                    self.frames.push(match ch {
+                        '{' => Frame::Object {
+                            key: None,
+                            after_key: false,
+                            entries: 0,
+                        },
+                        _ => Frame::Array(0),
+                    });
+                    self.extremes.depth.raise(self.frames.len(), offset);
+                }
+                '}' | ']' => {
+                    self.frames.pop();
+                }
+                ':' => {
+                    if let Some(Frame::Object { after_key, .. }) = self.frames.last_mut() {
+                        *after_key = true;
+                    }
+                }
+                ',' => match self.frames.last_mut() {
+                    Some(Frame::Array(index)) => {
+                        *index += 1;
+                        self.extremes.entries.raise(*index + 1, offset);
+                    }
+                    Some(Frame::Object {
+                        key,
+                        after_key,
+                        entries,
+                    }) => {
+                        *key = None;
+                        *after_key = false;
+                        *entries += 1;
+                        self.extremes.entries.raise(*entries + 1, offset);
+                    }
+                    None => {}
+                },
+                _ => {}
+            }
+        }
+    }
+
+    fn pointer(&self) -> String {
+        let mut path = String::new();
+        for frame in &self.frames {
+            match frame {
+                Frame::Object { key: Some(key), .. } => patch::push_token(&mut path, key),
+                Frame::Object { key: None, .. } => {}
+                Frame::Array(index) => patch::push_token(&mut path, &index.to_string()),
+            }
+        }
+        path
+    }
+}
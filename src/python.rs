@@ -0,0 +1,239 @@
+//! Handlers for Python literals, registered when `ParserOptions::allow_python_literals` is
+//! set: `True`/`False`/`None`, tuples, and string literals with a prefix (`r'..'`, `u'..'`)
+//! or triple quotes, as printed by models that "think in Python".
+
+use crate::{
+    FuzzyJsonError, JsonContext, ParseState, StateHandler, StringHandler, drop_dangling_comma,
+    push_string_char, valid_escape_len,
+};
+
+/// `True`, `False` and `None`.
+#[derive(Debug)]
+pub struct PythonLiteralHandler;
+
+impl PythonLiteralHandler {
+    const LITERALS: [(&'static str, &'static str); 3] =
+        [("True", "true"), ("False", "false"), ("None", "null")];
+
+    fn literal(state: &ParseState) -> Option<(&'static str, &'static str)> {
+        let remaining = state.remaining();
+        Self::LITERALS.into_iter().find(|(python, _)| {
+            remaining.starts_with(python)
+                && !remaining[python.len()..].starts_with(|c: char| c.is_alphanumeric() || c == '_')
+        })
+    }
+}
+
+impl StateHandler for PythonLiteralHandler {
+    fn can_handle(&self, state: &ParseState) -> bool {
+        (state.current_context() == &JsonContext::Array
+            || state.current_context() == &JsonContext::Colon
+            || state.current_context().is_key())
+            && Self::literal(state).is_some()
+    }
+
+    fn handle(&self, state: &mut ParseState) -> Result<bool, FuzzyJsonError> {
+        if let Some((python, json)) = Self::literal(state) {
+            state.output.push_str(json);
+            state.advance(python.len());
+        }
+        if state.current_context() != &JsonContext::Array {
+            state.pop_context();
+        }
+        Ok(true)
+    }
+}
+
+/// Tuples, which become arrays: `(1, 2)` and the one element `(1,)`.
+#[derive(Debug)]
+pub struct TupleHandler;
+
+impl StateHandler for TupleHandler {
+    fn can_handle(&self, state: &ParseState) -> bool {
+        match state.current_char() {
+            Some('(') => state.current_context() != &JsonContext::Object,
+            Some(')') => state.current_context() == &JsonContext::Array,
+            _ => false,
+        }
+    }
+
+    fn handle(&self, state: &mut ParseState) -> Result<bool, FuzzyJsonError> {
+        if state.current_context() == &JsonContext::Colon {
+            state.pop_context();
+        }
+        if state.current_char() == Some('(') {
+            state.output.push('[');
+            state.push_context(JsonContext::Array);
+        } else {
+            drop_dangling_comma(state);
+            state.output.push(']');
+            state.pop_context();
+        }
+        state.advance(1);
+        Ok(true)
+    }
+}
+
+/// String literals with a prefix (`r'C:\dir'`, `u'text'`, `b'..'`, `f'..'`) or triple
+/// quotes. Raw strings keep their backslashes, triple-quoted ones their line breaks.
+#[derive(Debug)]
+pub struct PythonStringHandler {
+    /// See `StringHandler::allow_comments`.
+    pub allow_comments: bool,
+}
+
+/// The quoting of a Python string literal, kept on the state while the literal is open.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PythonString {
+    quote: char,
+    triple: bool,
+    raw: bool,
+}
+
+impl PythonString {
+    fn delimiter_len(&self) -> usize {
+        if self.triple { 3 } else { 1 }
+    }
+
+    /// Whether the input stops at a quote too soon to tell if it ends the string, see
+    /// `StringHandler::lookahead_cut_off`.
+    pub(crate) fn awaits_lookahead(&self, strings: &StringHandler, state: &ParseState) -> bool {
+        let remaining = state.remaining();
+        if !remaining.starts_with(self.quote) {
+            return false;
+        }
+        if self.triple {
+            remaining.len() < self.delimiter_len() && remaining.chars().all(|c| c == self.quote)
+        } else {
+            strings.lookahead_cut_off(state)
+        }
+    }
+}
+
+impl PythonStringHandler {
+    /// The prefix length and quoting of the literal starting at the current position.
+    fn literal(state: &ParseState) -> Option<(usize, PythonString)> {
+        let remaining = state.remaining();
+        let prefix_len = remaining
+            .bytes()
+            .take(2)
+            .take_while(|b| matches!(b.to_ascii_lowercase(), b'r' | b'u' | b'b' | b'f'))
+            .count();
+        let quote = remaining[prefix_len..].chars().next()?;
+        if quote != '\'' && quote != '"' {
+            return None;
+        }
+        let triple = remaining[prefix_len..].starts_with(&quote.to_string().repeat(3));
+        if prefix_len == 0 && !triple {
+            // a plain string, left to `StringHandler`
+            return None;
+        }
+        let literal = PythonString {
+            quote,
+            triple,
+            raw: remaining[..prefix_len].contains(['r', 'R']),
+        };
+        Some((prefix_len, literal))
+    }
+
+    /// Copies the string body up to and including its closing delimiter. When the input
+    /// runs out first, or stops at an escape for `InvalidEscapeStrategy`, the literal is
+    /// left open on the state for the next step to carry on with.
+    fn consume_string(
+        &self,
+        state: &mut ParseState,
+        literal: PythonString,
+    ) -> Result<(), FuzzyJsonError> {
+        let strings = StringHandler {
+            allow_comments: self.allow_comments,
+            allow_single_quotes: true,
+        };
+        let delimiter = literal.quote.to_string().repeat(literal.delimiter_len());
+        let start = state.position;
+        while let Some(ch) = state.current_char() {
+            if state.position > start && literal.awaits_lookahead(&strings, state) {
+                break;
+            }
+            if state.remaining().starts_with(&delimiter)
+                && (literal.triple || strings.closes_string(state))
+            {
+                state.output.push('"');
+                state.advance(delimiter.len());
+                if state.is_value() {
+                    state.pop_context();
+                }
+                return Ok(());
+            }
+
+            if ch == '\\' && literal.raw {
+                state.output.push_str("\\\\");
+                state.advance(1);
+                // `\'` doesn't end a raw string either, but keeps its backslash
+                if state.current_char() == Some(literal.quote) {
+                    state
+                        .output
+                        .push_str(if literal.quote == '"' { "\\\"" } else { "'" });
+                    state.advance(1);
+                }
+            } else if ch == '\\' {
+                let Some(len) = valid_escape_len(state.remaining()) else {
+                    state.open_string = Some(literal.quote);
+                    state.python_string = Some(literal);
+                    return Err(FuzzyJsonError::InvalidEscape {
+                        location: state.error_location(),
+                    });
+                };
+                state
+                    .output
+                    .push_str(&state.input[state.position..state.position + len]);
+                state.position += len;
+            } else if ch == '"' {
+                state.output.push_str("\\\"");
+                state.advance(1);
+            } else {
+                push_string_char(&mut state.output, ch);
+                state.advance(1);
+            }
+        }
+        state.open_string = Some(literal.quote);
+        state.python_string = Some(literal);
+        Ok(())
+    }
+}
+
+impl PythonStringHandler {
+    /// Whether the input stops in the opening quotes of a literal, before it can tell
+    /// whether they are triple quotes.
+    pub(crate) fn opening_cut_off(state: &ParseState) -> bool {
+        let remaining = state.remaining();
+        let quotes = remaining
+            .trim_start_matches(|c: char| matches!(c.to_ascii_lowercase(), 'r' | 'u' | 'b' | 'f'));
+        state.open_string.is_none()
+            && !state.is_key_or_value()
+            && remaining.len() - quotes.len() <= 2
+            && matches!(quotes, "'" | "''" | "\"" | "\"\"")
+    }
+}
+
+impl StateHandler for PythonStringHandler {
+    fn can_handle(&self, state: &ParseState) -> bool {
+        !state.is_key_or_value() && Self::literal(state).is_some()
+    }
+
+    fn handle(&self, state: &mut ParseState) -> Result<bool, FuzzyJsonError> {
+        if let Some(literal) = state.python_string.take() {
+            // resuming a literal cut off by the end of a streamed chunk or an invalid escape
+            state.open_string = None;
+            self.consume_string(state, literal)?;
+            return Ok(true);
+        }
+        let Some((prefix_len, literal)) = Self::literal(state) else {
+            return Ok(true);
+        };
+        state.advance(prefix_len + literal.delimiter_len());
+        state.output.push('"');
+        StringHandler::enter_string_context(state, literal.quote);
+        self.consume_string(state, literal)?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,49 @@
+use std::ops::Range;
+
+/// One repair strategy firing while the input was being fixed up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepairRecord {
+    /// `RepairStrategy::name()` of the strategy that fired.
+    pub strategy: &'static str,
+    /// Byte range of the original input the repair consumed. Empty for pure insertions,
+    /// e.g. closing brackets of truncated input.
+    pub span: Range<usize>,
+    /// Text the repair dropped: the consumed input plus any output it took back
+    /// (such as a dangling comma).
+    pub removed: String,
+    /// Text the repair added to the output.
+    pub inserted: String,
+    /// The strategy flagged this repair as discarding input or inventing values,
+    /// see `RepairStrategy::is_lossy`.
+    pub lossy: bool,
+    /// JSON Pointer to the value the repair was made in, see `ParseState::path`.
+    pub path: String,
+}
+
+/// What `FuzzyJsonParser::parse_with_report` had to do to turn the input into JSON.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepairReport {
+    /// The input parsed as standard JSON without running the repair pipeline.
+    pub already_valid: bool,
+    /// Every repair strategy that fired, in the order they were applied.
+    pub repairs: Vec<RepairRecord>,
+}
+
+impl RepairReport {
+    pub(crate) fn valid() -> Self {
+        Self {
+            already_valid: true,
+            repairs: Vec::new(),
+        }
+    }
+
+    /// Names of the strategies that fired, in order.
+    pub fn strategies(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.repairs.iter().map(|record| record.strategy)
+    }
+
+    /// Whether any applied repair discarded input or invented values.
+    pub fn is_lossy(&self) -> bool {
+        self.repairs.iter().any(|record| record.lossy)
+    }
+}
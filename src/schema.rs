@@ -0,0 +1,339 @@
+use crate::patch::push_token;
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+/// A way the repaired value still breaks the schema set with
+/// `FuzzyJsonParserBuilder::with_schema`, after every fix-up that could be made.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{keyword} at `{path}`: {message}")]
+pub struct SchemaViolation {
+    /// JSON Pointer of the offending value, `""` for the document itself.
+    pub path: String,
+    /// Schema keyword that failed, e.g. `required` or `enum`.
+    pub keyword: &'static str,
+    pub message: String,
+}
+
+/// Fixes `value` up to match `schema` where it can and returns whatever still doesn't.
+///
+/// Understands the keywords tool-call schemas are usually written with: `type`, `enum`,
+/// `const`, `properties`, `required`, `additionalProperties`, `items`, the length and range
+/// bounds, `allOf`/`anyOf`/`oneOf` and local `$ref`s into `definitions` or `$defs`.
+pub(crate) fn validate(schema: &Value, value: &mut Value) -> Vec<SchemaViolation> {
+    let mut validator = Validator {
+        root: schema,
+        path: String::new(),
+        references: Vec::new(),
+        violations: Vec::new(),
+    };
+    validator.visit(schema, value);
+    validator.violations
+}
+
+struct Validator<'a> {
+    root: &'a Value,
+    path: String,
+    /// `$ref`s being followed and the path each was met at, to catch one that leads back to
+    /// itself without going any deeper into the value.
+    references: Vec<(&'a str, String)>,
+    violations: Vec<SchemaViolation>,
+}
+
+impl<'a> Validator<'a> {
+    fn visit(&mut self, schema: &'a Value, value: &mut Value) {
+        let schema = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => return self.violation("false", "no value is allowed here"),
+            Value::Object(schema) => schema,
+            _ => return,
+        };
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            self.follow(reference, value);
+        }
+
+        if let Some(types) = schema.get("type")
+            && !self.check_type(types, value)
+        {
+            // the keywords below assume the right type, so there is nothing more to check
+            return;
+        }
+
+        if let Some(Value::Array(options)) = schema.get("enum")
+            && match_enum(options, value).is_none()
+        {
+            let options = Value::Array(options.clone());
+            self.violation("enum", format!("{value} is not one of {options}"));
+        }
+        if let Some(expected) = schema.get("const")
+            && value != expected
+        {
+            self.violation("const", format!("expected {expected}, found {value}"));
+        }
+
+        if let Some(Value::Array(branches)) = schema.get("allOf") {
+            for branch in branches {
+                self.visit(branch, value);
+            }
+        }
+        if let Some(Value::Array(branches)) = schema.get("anyOf") {
+            self.any_of(branches, value);
+        }
+        if let Some(Value::Array(branches)) = schema.get("oneOf") {
+            self.one_of(branches, value);
+        }
+
+        match value {
+            Value::Object(entries) => self.visit_object(schema, entries),
+            Value::Array(items) => self.visit_array(schema, items),
+            Value::String(text) => self.check_bounds(
+                schema,
+                "minLength",
+                "maxLength",
+                text.chars().count(),
+                "characters",
+            ),
+            Value::Number(number) => self.check_range(schema, number.as_f64().unwrap_or(0.0)),
+            _ => {}
+        }
+    }
+
+    fn visit_object(&mut self, schema: &'a Map<String, Value>, entries: &mut Map<String, Value>) {
+        let properties = schema.get("properties").and_then(Value::as_object);
+
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for name in required.iter().filter_map(Value::as_str) {
+                if entries.contains_key(name) {
+                    continue;
+                }
+                let default = properties
+                    .and_then(|properties| properties.get(name))
+                    .and_then(|property| property.get("default"));
+                match default {
+                    Some(default) => {
+                        entries.insert(name.to_string(), default.clone());
+                    }
+                    None => self.violation("required", format!("missing property `{name}`")),
+                }
+            }
+        }
+
+        let additional = schema.get("additionalProperties");
+        if additional == Some(&Value::Bool(false)) {
+            entries.retain(|name, _| {
+                properties.is_some_and(|properties| properties.contains_key(name))
+            });
+        }
+
+        let len = self.path.len();
+        for (name, entry) in entries.iter_mut() {
+            let property = properties
+                .and_then(|properties| properties.get(name))
+                .or(additional);
+            if let Some(property) = property {
+                push_token(&mut self.path, name);
+                self.visit(property, entry);
+                self.path.truncate(len);
+            }
+        }
+    }
+
+    fn visit_array(&mut self, schema: &'a Map<String, Value>, items: &mut [Value]) {
+        self.check_bounds(schema, "minItems", "maxItems", items.len(), "items");
+
+        if let Some(item_schema) = schema.get("items") {
+            let len = self.path.len();
+            for (index, item) in items.iter_mut().enumerate() {
+                push_token(&mut self.path, &index.to_string());
+                self.visit(item_schema, item);
+                self.path.truncate(len);
+            }
+        }
+    }
+
+    /// Checks `type`, coercing strings that hold a number or boolean the schema asks for.
+    fn check_type(&mut self, types: &Value, value: &mut Value) -> bool {
+        let types: Vec<&str> = match types {
+            Value::String(name) => vec![name],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => return true,
+        };
+        if types.iter().any(|name| has_type(value, name)) {
+            return true;
+        }
+        if let Some(coerced) = types.iter().find_map(|name| coerce(value, name)) {
+            *value = coerced;
+            return true;
+        }
+        self.violation(
+            "type",
+            format!("expected {}, found {value}", types.join(" or ")),
+        );
+        false
+    }
+
+    fn follow(&mut self, reference: &'a str, value: &mut Value) {
+        let Some(target) = self.resolve(reference) else {
+            return self.violation("$ref", format!("cannot resolve `{reference}`"));
+        };
+        let seen = self
+            .references
+            .iter()
+            .any(|(seen, path)| *seen == reference && *path == self.path);
+        if seen {
+            return self.violation("$ref", format!("`{reference}` refers back to itself"));
+        }
+        self.references.push((reference, self.path.clone()));
+        self.visit(target, value);
+        self.references.pop();
+    }
+
+    fn any_of(&mut self, branches: &'a [Value], value: &mut Value) {
+        // take the fix-ups of the first branch that accepts the value
+        for branch in branches {
+            if let Some(candidate) = self.try_branch(branch, value) {
+                *value = candidate;
+                return;
+            }
+        }
+        self.violation("anyOf", format!("{value} matches none of the schemas"));
+    }
+
+    fn one_of(&mut self, branches: &'a [Value], value: &mut Value) {
+        let mut accepted: Vec<Value> = branches
+            .iter()
+            .filter_map(|branch| self.try_branch(branch, value))
+            .collect();
+        match accepted.len() {
+            0 => self.violation("oneOf", format!("{value} matches none of the schemas")),
+            1 => *value = accepted.remove(0),
+            matches => self.violation(
+                "oneOf",
+                format!("{value} matches {matches} of the schemas, expected exactly one"),
+            ),
+        }
+    }
+
+    /// `value` with the fix-ups of `branch`, if that leaves no violations.
+    fn try_branch(&self, branch: &'a Value, value: &Value) -> Option<Value> {
+        let mut candidate = value.clone();
+        let mut branch_validator = Validator {
+            root: self.root,
+            path: self.path.clone(),
+            references: self.references.clone(),
+            violations: Vec::new(),
+        };
+        branch_validator.visit(branch, &mut candidate);
+        branch_validator.violations.is_empty().then_some(candidate)
+    }
+
+    fn check_bounds(
+        &mut self,
+        schema: &Map<String, Value>,
+        min_keyword: &'static str,
+        max_keyword: &'static str,
+        len: usize,
+        unit: &str,
+    ) {
+        if let Some(min) = schema.get(min_keyword).and_then(Value::as_u64)
+            && (len as u64) < min
+        {
+            self.violation(
+                min_keyword,
+                format!("{len} {unit}, expected at least {min}"),
+            );
+        }
+        if let Some(max) = schema.get(max_keyword).and_then(Value::as_u64)
+            && (len as u64) > max
+        {
+            self.violation(max_keyword, format!("{len} {unit}, expected at most {max}"));
+        }
+    }
+
+    fn check_range(&mut self, schema: &Map<String, Value>, number: f64) {
+        let bound = |keyword| schema.get(keyword).and_then(Value::as_f64);
+        if let Some(min) = bound("minimum")
+            && number < min
+        {
+            self.violation("minimum", format!("{number} is less than {min}"));
+        }
+        if let Some(max) = bound("maximum")
+            && number > max
+        {
+            self.violation("maximum", format!("{number} is greater than {max}"));
+        }
+        if let Some(min) = bound("exclusiveMinimum")
+            && number <= min
+        {
+            self.violation(
+                "exclusiveMinimum",
+                format!("{number} is not greater than {min}"),
+            );
+        }
+        if let Some(max) = bound("exclusiveMaximum")
+            && number >= max
+        {
+            self.violation(
+                "exclusiveMaximum",
+                format!("{number} is not less than {max}"),
+            );
+        }
+    }
+
+    fn resolve(&self, reference: &str) -> Option<&'a Value> {
+        let pointer = reference.strip_prefix('#')?;
+        self.root.pointer(pointer)
+    }
+
+    fn violation(&mut self, keyword: &'static str, message: impl Into<String>) {
+        self.violations.push(SchemaViolation {
+            path: self.path.clone(),
+            keyword,
+            message: message.into(),
+        });
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "string" => value.is_string(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        "number" => value.is_number(),
+        "integer" => value.as_f64().is_some_and(|number| number.fract() == 0.0),
+        _ => true,
+    }
+}
+
+fn coerce(value: &Value, name: &str) -> Option<Value> {
+    let text = value.as_str()?.trim();
+    match name {
+        "number" => text.parse().ok().map(Value::Number),
+        "integer" => text
+            .parse::<i64>()
+            .map(Value::from)
+            .or_else(|_| text.parse::<u64>().map(Value::from))
+            .ok(),
+        "boolean" if text.eq_ignore_ascii_case("true") => Some(Value::Bool(true)),
+        "boolean" if text.eq_ignore_ascii_case("false") => Some(Value::Bool(false)),
+        _ => None,
+    }
+}
+
+/// Accepts `value` if it is one of `options`, replacing a string that only differs in case
+/// with the spelling the schema uses.
+fn match_enum(options: &[Value], value: &mut Value) -> Option<()> {
+    if options.contains(value) {
+        return Some(());
+    }
+    let text = value.as_str()?;
+    let option = options.iter().find(|option| {
+        option
+            .as_str()
+            .is_some_and(|option| option.eq_ignore_ascii_case(text))
+    })?;
+    *value = option.clone();
+    Some(())
+}
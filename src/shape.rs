@@ -0,0 +1,297 @@
+use crate::patch::push_token;
+use serde_json::{Map, Number, Value};
+use std::collections::{BTreeMap, HashMap};
+use thiserror::Error;
+
+/// Shape a repaired document is expected to have, used by `FuzzyJsonParser::parse_guided`
+/// to bend the repaired value into something the target type deserializes from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    /// Anything goes, the value is kept as is.
+    Any,
+    Bool,
+    Integer,
+    Number,
+    String,
+    /// `null` or the inner shape.
+    Optional(Box<Shape>),
+    /// Array whose elements all have the inner shape.
+    Array(Box<Shape>),
+    /// Object with arbitrary keys whose values all have the inner shape.
+    Map(Box<Shape>),
+    /// Object with a fixed set of fields; any other key is dropped.
+    Object(Vec<(String, Shape)>),
+}
+
+/// Types that can tell the parser which shape they deserialize from.
+///
+/// Implemented for primitives, `String`, `Option`, `Vec`, string-keyed maps and `Value`.
+/// Structs describe their fields with `Shape::object`:
+///
+/// ```
+/// use chill_json::{ExpectedShape, Shape};
+///
+/// struct Person {
+///     name: String,
+///     tags: Vec<String>,
+/// }
+///
+/// impl ExpectedShape for Person {
+///     fn shape() -> Shape {
+///         Shape::object([("name", String::shape()), ("tags", Vec::<String>::shape())])
+///     }
+/// }
+/// ```
+pub trait ExpectedShape {
+    fn shape() -> Shape;
+}
+
+impl Shape {
+    pub fn object<K: Into<String>>(fields: impl IntoIterator<Item = (K, Shape)>) -> Self {
+        Shape::Object(
+            fields
+                .into_iter()
+                .map(|(name, shape)| (name.into(), shape))
+                .collect(),
+        )
+    }
+
+    /// Value filled in for a field that is missing, `null`, or was cut off.
+    pub fn default_value(&self) -> Value {
+        match self {
+            Shape::Any | Shape::Optional(_) => Value::Null,
+            Shape::Bool => Value::Bool(false),
+            Shape::Integer | Shape::Number => Value::from(0),
+            Shape::String => Value::String(String::new()),
+            Shape::Array(_) => Value::Array(Vec::new()),
+            Shape::Map(_) => Value::Object(Map::new()),
+            Shape::Object(fields) => Value::Object(
+                fields
+                    .iter()
+                    .map(|(name, shape)| (name.clone(), shape.default_value()))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Bends `value` into this shape where that loses nothing, and returns the values that
+    /// don't fit.
+    ///
+    /// Numbers and booleans written as strings are parsed, integral floats such as `2.0`
+    /// become integers, a lone value where an array is expected gets wrapped in one, `null`
+    /// and fields missing from an object get their default, and keys the shape doesn't know
+    /// about are dropped. Anything else that doesn't fit is kept as it is and reported.
+    pub fn conform(&self, value: Value) -> (Value, Vec<ShapeMismatch>) {
+        self.conform_repaired(value, &[])
+    }
+
+    /// `conform` for a value repair made up some of, at the JSON Pointers in `invented`,
+    /// like the `0` truncation repair puts after a dangling key. Made up values that don't
+    /// fit get the default rather than being reported.
+    pub(crate) fn conform_repaired(
+        &self,
+        value: Value,
+        invented: &[&str],
+    ) -> (Value, Vec<ShapeMismatch>) {
+        let mut conformer = Conformer {
+            invented,
+            path: String::new(),
+            mismatches: Vec::new(),
+        };
+        let value = conformer.visit(self, value);
+        (value, conformer.mismatches)
+    }
+
+    /// What a value of this shape is, for mismatch messages.
+    fn describe(&self) -> &'static str {
+        match self {
+            Shape::Any => "anything",
+            Shape::Bool => "a boolean",
+            Shape::Integer => "an integer",
+            Shape::Number => "a number",
+            Shape::String => "a string",
+            Shape::Optional(inner) => inner.describe(),
+            Shape::Array(_) => "an array",
+            Shape::Map(_) | Shape::Object(_) => "an object",
+        }
+    }
+}
+
+/// A value `Shape::conform` kept as it was because it doesn't fit the shape.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("expected {expected} at `{path}`, found {found}")]
+pub struct ShapeMismatch {
+    /// JSON Pointer of the value, `""` for the document itself.
+    pub path: String,
+    /// What the shape asked for, e.g. `an integer`.
+    pub expected: &'static str,
+    /// The value as it was kept.
+    pub found: Value,
+}
+
+struct Conformer<'a> {
+    invented: &'a [&'a str],
+    path: String,
+    mismatches: Vec<ShapeMismatch>,
+}
+
+impl Conformer<'_> {
+    fn visit(&mut self, shape: &Shape, value: Value) -> Value {
+        match self.coerce(shape, value) {
+            Ok(value) => value,
+            Err(_) if self.invented.contains(&self.path.as_str()) => shape.default_value(),
+            Err(found) => {
+                self.mismatches.push(ShapeMismatch {
+                    path: self.path.clone(),
+                    expected: shape.describe(),
+                    found: found.clone(),
+                });
+                found
+            }
+        }
+    }
+
+    /// The value in `shape`, or back as it was if it can't be turned into one without
+    /// losing something.
+    fn coerce(&mut self, shape: &Shape, value: Value) -> Result<Value, Value> {
+        match (shape, value) {
+            (Shape::Any, value) => Ok(value),
+            (Shape::Optional(_), Value::Null) => Ok(Value::Null),
+            (Shape::Optional(inner), value) => self.coerce(inner, value),
+            (shape, Value::Null) => Ok(shape.default_value()),
+
+            (Shape::Bool, Value::Bool(flag)) => Ok(Value::Bool(flag)),
+            (Shape::Bool, Value::String(text)) => match text.as_str() {
+                "true" => Ok(Value::Bool(true)),
+                "false" => Ok(Value::Bool(false)),
+                _ => Err(Value::String(text)),
+            },
+
+            (Shape::Integer, Value::Number(number)) => {
+                integer(&number).ok_or(Value::Number(number))
+            }
+            (Shape::Integer, Value::String(text)) => text
+                .parse()
+                .ok()
+                .and_then(|number| integer(&number))
+                .ok_or(Value::String(text)),
+            (Shape::Number, Value::Number(number)) => Ok(Value::Number(number)),
+            (Shape::Number, Value::String(text)) => {
+                text.parse().map(Value::Number).or(Err(Value::String(text)))
+            }
+
+            (Shape::String, Value::String(text)) => Ok(Value::String(text)),
+
+            (Shape::Array(element), Value::Array(items)) => {
+                let len = self.path.len();
+                let items = items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, item)| {
+                        push_token(&mut self.path, &index.to_string());
+                        let item = self.visit(element, item);
+                        self.path.truncate(len);
+                        item
+                    })
+                    .collect();
+                Ok(Value::Array(items))
+            }
+            (Shape::Array(element), value) => {
+                let len = self.path.len();
+                push_token(&mut self.path, "0");
+                let item = self.coerce(element, value);
+                self.path.truncate(len);
+                item.map(|item| Value::Array(vec![item]))
+            }
+
+            (Shape::Map(inner), Value::Object(entries)) => {
+                let len = self.path.len();
+                let entries = entries
+                    .into_iter()
+                    .map(|(key, item)| {
+                        push_token(&mut self.path, &key);
+                        let item = self.visit(inner, item);
+                        self.path.truncate(len);
+                        (key, item)
+                    })
+                    .collect();
+                Ok(Value::Object(entries))
+            }
+
+            (Shape::Object(fields), Value::Object(mut entries)) => {
+                let len = self.path.len();
+                let entries = fields
+                    .iter()
+                    .map(|(name, shape)| {
+                        let value = match entries.remove(name) {
+                            Some(value) => {
+                                push_token(&mut self.path, name);
+                                let value = self.visit(shape, value);
+                                self.path.truncate(len);
+                                value
+                            }
+                            None => shape.default_value(),
+                        };
+                        (name.clone(), value)
+                    })
+                    .collect();
+                Ok(Value::Object(entries))
+            }
+
+            (_, value) => Err(value),
+        }
+    }
+}
+
+/// Integral numbers, including floats with no fractional part such as `3.0`.
+fn integer(number: &Number) -> Option<Value> {
+    if number.is_i64() || number.is_u64() {
+        return Some(Value::Number(number.clone()));
+    }
+    let float = number.as_f64()?;
+    if float.fract() == 0.0 && float.abs() < i64::MAX as f64 {
+        Some(Value::from(float as i64))
+    } else {
+        None
+    }
+}
+
+macro_rules! expected_shape {
+    ($shape:expr => $($ty:ty),+) => {
+        $(impl ExpectedShape for $ty {
+            fn shape() -> Shape {
+                $shape
+            }
+        })+
+    };
+}
+
+expected_shape!(Shape::Bool => bool);
+expected_shape!(Shape::Integer => i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+expected_shape!(Shape::Number => f32, f64);
+expected_shape!(Shape::String => String);
+expected_shape!(Shape::Any => Value);
+
+impl<T: ExpectedShape> ExpectedShape for Option<T> {
+    fn shape() -> Shape {
+        Shape::Optional(Box::new(T::shape()))
+    }
+}
+
+impl<T: ExpectedShape> ExpectedShape for Vec<T> {
+    fn shape() -> Shape {
+        Shape::Array(Box::new(T::shape()))
+    }
+}
+
+impl<T: ExpectedShape, S> ExpectedShape for HashMap<String, T, S> {
+    fn shape() -> Shape {
+        Shape::Map(Box::new(T::shape()))
+    }
+}
+
+impl<T: ExpectedShape> ExpectedShape for BTreeMap<String, T> {
+    fn shape() -> Shape {
+        Shape::Map(Box::new(T::shape()))
+    }
+}
@@ -0,0 +1,244 @@
+use crate::partial::PartialValue;
+use crate::patch::{self, PatchOperation};
+use crate::{
+    BacktickStringHandler, FuzzyJsonError, FuzzyJsonParser, JsonContext, ParseState,
+    PythonStringHandler, RepairRecord, StringHandler, TrimStrayContentInBeginningStrategy,
+    valid_escape_len,
+};
+use serde_json::Value;
+
+/// Longest lookahead any handler needs to recognise its token (`undefined`, "```json").
+/// A step that fails closer than this to the end of the buffered input may just be missing
+/// the rest of its token, so it is retried once more input arrives.
+const MAX_TOKEN_LOOKAHEAD: usize = 16;
+
+/// Incremental parser for input that arrives in chunks, created by
+/// `FuzzyJsonParser::streaming`.
+///
+/// Each `push` only runs the handlers over the new chunk (plus the token the previous chunk
+/// cut off), keeping the `ParseState` stack and output between calls, so feeding a whole
+/// response costs about the same as a single `parse_value`. `current_value` keeps the value
+/// built from the output it has already seen and only reads what was added since, plus the
+/// closing of the scopes still open.
+#[derive(Debug)]
+pub struct StreamingParser<'a> {
+    parser: &'a FuzzyJsonParser,
+    state: ParseState,
+    /// Escape sequence the last chunk ended in the middle of, held back until we know what
+    /// it escapes, see `pending_escape_start`.
+    held_back: String,
+    stopped: bool,
+    /// Value of the output settled so far, see `current_value`.
+    partial: PartialValue,
+    /// Snapshot the last `push_diff` was computed against.
+    last_value: Option<Value>,
+}
+
+impl<'a> StreamingParser<'a> {
+    pub(crate) fn new(parser: &'a FuzzyJsonParser) -> Self {
+        Self {
+            parser,
+            state: ParseState::new(String::new()),
+            held_back: String::new(),
+            stopped: false,
+            partial: PartialValue::default(),
+            last_value: None,
+        }
+    }
+
+    /// Feeds the next chunk of input.
+    pub fn push(&mut self, chunk: &str) -> Result<(), FuzzyJsonError> {
+        let size = self.state.input.len() + self.held_back.len() + chunk.len();
+        self.parser.check_input_size(size)?;
+        self.held_back.push_str(chunk);
+        let ready = pending_escape_start(&self.held_back);
+        self.state.input.push_str(&self.held_back[..ready]);
+        self.held_back.drain(..ready);
+        self.parse_buffered().map_err(|e| self.state.locate(e))
+    }
+
+    /// Steps through the input pushed so far, stopping short of a token the next chunk may
+    /// still change.
+    fn parse_buffered(&mut self) -> Result<(), FuzzyJsonError> {
+        let strings = StringHandler {
+            allow_comments: self.parser.options.allow_comments,
+            allow_single_quotes: self.parser.options.allow_single_quotes,
+        };
+        while !self.stopped && !self.state.remaining().trim_start().is_empty() {
+            // whether the quote ends the string depends on what the next chunk starts with
+            if strings.awaits_lookahead(&self.state) {
+                break;
+            }
+            // and whether a Python literal is triple-quoted on the next quotes
+            if self.parser.options.allow_python_literals
+                && PythonStringHandler::opening_cut_off(&self.state)
+            {
+                break;
+            }
+            // or a `(` opens a tuple rather than an aside in the preamble
+            if self.parser.options.allow_python_literals
+                && TrimStrayContentInBeginningStrategy::tuple_cut_off(&self.state)
+            {
+                break;
+            }
+            // or a backtick opens a string rather than the closing fence
+            if self.parser.options.allow_backtick_strings
+                && BacktickStringHandler::fence_cut_off(&self.state)
+            {
+                break;
+            }
+            self.parser.check_limits(&mut self.state)?;
+            if self.skip_preamble()? {
+                continue;
+            }
+
+            let position = self.state.position;
+            let checkpoint = self.state.checkpoint();
+            match self.parser.step(&mut self.state, "") {
+                // the token may carry on in the next chunk, so it is parsed again then
+                Ok(_) if self.state.open_string.is_none() && self.near_end(position) => {
+                    self.state.rewind(checkpoint);
+                    break;
+                }
+                Ok(should_continue) => self.stopped = !should_continue,
+                Err(e) => {
+                    self.state.rewind(checkpoint);
+                    if self.state.remaining().len() < MAX_TOKEN_LOOKAHEAD {
+                        break;
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Feeds the next chunk of input and returns the JSON Patch that takes the value from
+    /// the previous `push_diff` to the current one, see `patch::diff`.
+    pub fn push_diff(&mut self, chunk: &str) -> Result<Vec<PatchOperation>, FuzzyJsonError> {
+        self.push(chunk)?;
+        match self.current_value()?.cloned() {
+            Some(value) => Ok(self.diff_to(value)),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Best-effort value of everything pushed so far, with open strings, arrays and objects
+    /// closed the way truncated input is. `None` until a document has started.
+    pub fn current_value(&mut self) -> Result<Option<&Value>, FuzzyJsonError> {
+        let output = &self.state.output;
+        let settled = output
+            .trim_end_matches(|c: char| c.is_whitespace() || c == ',')
+            .len();
+        self.partial.settle(&output[..settled])?;
+
+        // the unparsed tail is usually a number or literal still being written, but if it
+        // can't be made sense of yet, fall back to what has been parsed for sure
+        let previewed = self.preview(true).or_else(|_| self.preview(false));
+        previewed.map_err(|e| self.state.locate(e))?;
+        Ok(self.partial.value())
+    }
+    /// Completes the parse once the whole input has been pushed.
+    pub fn finish(self) -> Result<Value, FuzzyJsonError> {
+        self.finish_with_repairs().map(|(value, _)| value)
+    }
+
+    /// Completes the parse like `finish`, along with every repair the input needed.
+    pub(crate) fn finish_with_repairs(
+        mut self,
+    ) -> Result<(Value, Vec<RepairRecord>), FuzzyJsonError> {
+        self.state.input.push_str(&self.held_back);
+        if !self.stopped {
+            // a preamble held back on a cut-off token is dropped now that the input is complete
+            self.skip_preamble().map_err(|e| self.state.locate(e))?;
+            self.parser.run_to_end(&mut self.state, "")?;
+        }
+        let value = self.state.output_value()?;
+        Ok((value, self.state.repairs))
+    }
+
+    /// Completes the parse like `finish`, returning the last patch to apply along with the
+    /// final value.
+    pub fn finish_diff(mut self) -> Result<(Value, Vec<PatchOperation>), FuzzyJsonError> {
+        let last_value = self.last_value.take();
+        let value = self.finish()?;
+        let operations = patch::diff(last_value.as_ref(), &value);
+        Ok((value, operations))
+    }
+
+    fn diff_to(&mut self, value: Value) -> Vec<PatchOperation> {
+        let operations = patch::diff(self.last_value.as_ref(), &value);
+        self.last_value = Some(value);
+        operations
+    }
+
+    /// Closes the open scopes the way `finish` would, on the live state since the steps are
+    /// taken back afterwards, and previews the value with the output that added.
+    fn preview(&mut self, with_tail: bool) -> Result<(), FuzzyJsonError> {
+        let saved = self.state.save();
+        if with_tail {
+            self.state.input.push_str(&self.held_back);
+        } else {
+            self.state.position = self.state.input.len();
+        }
+        let mut closed = Ok(true);
+        while !self.stopped && matches!(closed, Ok(true)) {
+            closed = self.parser.run_step_unlocated(&mut self.state, "");
+        }
+        let tail = self
+            .state
+            .output
+            .get(self.partial.fed()..)
+            .map(str::to_string);
+        self.state.restore(saved);
+
+        closed?;
+        let tail = tail.ok_or_else(|| {
+            FuzzyJsonError::RepairFailed("closing took back settled output".to_string())
+        })?;
+        Ok(self.partial.preview(&tail)?)
+    }
+
+    /// Whether the last step, which started at `position`, may have decided on a token that
+    /// the next chunk would change: it ran into the end of the buffered input, or it repaired
+    /// something without consuming input while the lookahead its handlers need is missing.
+    fn near_end(&self, position: usize) -> bool {
+        let remaining = self.state.remaining();
+        remaining.trim_start().is_empty()
+            || (self.state.position == position && remaining.len() < MAX_TOKEN_LOOKAHEAD)
+    }
+
+    /// Drops whatever precedes the document, like the up-front repair in `repair_json`.
+    fn skip_preamble(&mut self) -> Result<bool, FuzzyJsonError> {
+        if !self.state.output.is_empty() || self.state.current_context() != &JsonContext::Root {
+            return Ok(false);
+        }
+        let position = self.state.position;
+        self.parser.try_repair_strategies(&mut self.state, "")?;
+        Ok(self.state.position > position)
+    }
+}
+
+/// Where the escape sequence `text` may end in the middle of starts, like `\`, `\u00` or a
+/// high surrogate still waiting for its low half, or `text.len()` if it doesn't end in one.
+fn pending_escape_start(text: &str) -> usize {
+    let mut from = 0;
+    while let Some(offset) = text[from..].find('\\') {
+        let start = from + offset;
+        let tail = &text[start..];
+        if let Some(len) = valid_escape_len(tail) {
+            from = start + len;
+            continue;
+        }
+        // `\uD83D\uDE0` is the longest escape that can still be completed
+        let completable = tail
+            .bytes()
+            .all(|b| b == b'\\' || b == b'u' || b == b'x' || b.is_ascii_hexdigit());
+        if tail.len() < 12 && completable {
+            return start;
+        }
+        // left to `InvalidEscapeStrategy`
+        from = start + 1;
+    }
+    text.len()
+}
@@ -0,0 +1,176 @@
+use chill_json::{FuzzyJsonError, FuzzyJsonParser, ParserOptions};
+use js_sys::{Array, BigInt, Object, Reflect};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+/// Largest integer a JS number holds exactly, anything beyond becomes a `BigInt`.
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// A configured parser, for repairing many documents with the same options.
+///
+/// Options use the camelCase names of `ParserOptions`, e.g.
+/// `new Parser({ allowComments: false, maxRepairAttempts: 100 })`.
+#[wasm_bindgen]
+pub struct Parser {
+    inner: FuzzyJsonParser,
+}
+
+#[wasm_bindgen]
+impl Parser {
+    #[wasm_bindgen(constructor)]
+    pub fn new(options: JsValue) -> Result<Parser, JsValue> {
+        Ok(Parser {
+            inner: FuzzyJsonParser::with_options(parser_options(&options)?),
+        })
+    }
+
+    /// Repairs `text` and returns the value as plain JS objects and arrays.
+    #[wasm_bindgen(js_name = parseValue)]
+    pub fn parse_value(&self, text: &str) -> Result<JsValue, JsValue> {
+        match self.inner.parse_value(text) {
+            Ok(value) => to_js_value(&value),
+            Err(error) => Err(to_js_error(error)),
+        }
+    }
+
+    /// Closes the strings, arrays and objects truncated `text` left open.
+    #[wasm_bindgen(js_name = aggressivelyCloseScopes)]
+    pub fn aggressively_close_scopes(&self, text: &str) -> Result<String, JsValue> {
+        self.inner
+            .aggressively_close_scopes(text)
+            .map_err(to_js_error)
+    }
+}
+
+/// `parseValue(text, options?)`, for one-off use without keeping a `Parser` around.
+#[wasm_bindgen(js_name = parseValue)]
+pub fn parse_value(text: &str, options: JsValue) -> Result<JsValue, JsValue> {
+    Parser::new(options)?.parse_value(text)
+}
+
+/// `aggressivelyCloseScopes(text, options?)`.
+#[wasm_bindgen(js_name = aggressivelyCloseScopes)]
+pub fn aggressively_close_scopes(text: &str, options: JsValue) -> Result<String, JsValue> {
+    Parser::new(options)?.aggressively_close_scopes(text)
+}
+
+fn parser_options(js_options: &JsValue) -> Result<ParserOptions, JsValue> {
+    let mut options = ParserOptions::default();
+    if js_options.is_undefined() || js_options.is_null() {
+        return Ok(options);
+    }
+    if !js_options.is_object() {
+        return Err(js_sys::TypeError::new("options must be an object").into());
+    }
+
+    let flags: [(&str, &mut bool); 10] = [
+        ("autoRepair", &mut options.auto_repair),
+        ("allowTrailingCommas", &mut options.allow_trailing_commas),
+        ("allowComments", &mut options.allow_comments),
+        ("allowSingleQuotes", &mut options.allow_single_quotes),
+        ("allowUnquotedKeys", &mut options.allow_unquoted_keys),
+        ("allowBarewordValues", &mut options.allow_bareword_values),
+        ("strictMode", &mut options.strict_mode),
+        (
+            "aggressiveTruncationRepair",
+            &mut options.aggressive_truncation_repair,
+        ),
+        ("allowBacktickStrings", &mut options.allow_backtick_strings),
+        ("allowPythonLiterals", &mut options.allow_python_literals),
+    ];
+    for (key, field) in flags {
+        let value = Reflect::get(js_options, &key.into())?;
+        if let Some(flag) = value.as_bool() {
+            *field = flag;
+        }
+    }
+    if let Some(max) = Reflect::get(js_options, &"maxRepairAttempts".into())?.as_f64() {
+        options.max_repair_attempts = max as usize;
+    }
+    // no `maxDuration`: `std::time::Instant` isn't available on wasm32-unknown-unknown
+    let limits: [(&str, &mut Option<usize>); 5] = [
+        ("maxInputBytes", &mut options.max_input_bytes),
+        ("maxDepth", &mut options.max_depth),
+        ("maxStringLength", &mut options.max_string_length),
+        ("maxEntries", &mut options.max_entries),
+        ("maxSteps", &mut options.max_steps),
+    ];
+    for (key, field) in limits {
+        if let Some(max) = Reflect::get(js_options, &key.into())?.as_f64() {
+            *field = Some(max as usize);
+        }
+    }
+    if let Some(chars) = Reflect::get(js_options, &"unquotedKeyChars".into())?.as_string() {
+        options.unquoted_key_chars = chars;
+    }
+    if let Some(policy) = Reflect::get(js_options, &"nonFiniteNumbers".into())?.as_string() {
+        options.non_finite_numbers = policy
+            .parse()
+            .map_err(|message: String| JsValue::from(js_sys::TypeError::new(&message)))?;
+    }
+    let schema = Reflect::get(js_options, &"schema".into())?;
+    if schema.is_object() {
+        let schema = String::from(js_sys::JSON::stringify(&schema)?);
+        options.schema = Some(
+            serde_json::from_str(&schema)
+                .map_err(|e| JsValue::from(JsError::new(&e.to_string())))?,
+        );
+    }
+
+    Ok(options)
+}
+
+fn to_js_value(value: &Value) -> Result<JsValue, JsValue> {
+    Ok(match value {
+        Value::Null => JsValue::NULL,
+        Value::Bool(b) => JsValue::from_bool(*b),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                if i.unsigned_abs() <= MAX_SAFE_INTEGER {
+                    JsValue::from_f64(i as f64)
+                } else {
+                    BigInt::from(i).into()
+                }
+            } else if let Some(u) = n.as_u64() {
+                BigInt::from(u).into()
+            } else {
+                JsValue::from_f64(n.as_f64().unwrap_or(f64::NAN))
+            }
+        }
+        Value::String(s) => JsValue::from_str(s),
+        Value::Array(arr) => {
+            let js_array = Array::new_with_length(arr.len() as u32);
+            for (i, elem) in arr.iter().enumerate() {
+                js_array.set(i as u32, to_js_value(elem)?);
+            }
+            js_array.into()
+        }
+        Value::Object(map) => {
+            let js_object = Object::new();
+            for (k, v) in map {
+                Reflect::set(&js_object, &JsValue::from_str(k), &to_js_value(v)?)?;
+            }
+            js_object.into()
+        }
+    })
+}
+
+/// A JS `Error` with `kind` set to the `FuzzyJsonError` variant name and, where the error
+/// has a location, `pos` (byte offset in the input), `line`, `column` and `path`.
+fn to_js_error(error: FuzzyJsonError) -> JsValue {
+    let js_error = js_sys::Error::new(&error.to_string());
+    // setting properties on a fresh error object can't fail
+    let _ = Reflect::set(&js_error, &"kind".into(), &error.kind().into());
+    if let Some(location) = error.location() {
+        let properties = [
+            ("pos", JsValue::from_f64(location.offset as f64)),
+            ("line", JsValue::from_f64(location.line as f64)),
+            ("column", JsValue::from_f64(location.column as f64)),
+            ("path", JsValue::from_str(&location.path)),
+        ];
+        for (name, value) in properties {
+            let _ = Reflect::set(&js_error, &name.into(), &value);
+        }
+    }
+    js_error.into()
+}
@@ -0,0 +1,71 @@
+use chill_json_wasm::{Parser, aggressively_close_scopes, parse_value};
+use js_sys::{BigInt, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn get(target: &JsValue, key: &str) -> JsValue {
+    Reflect::get(target, &key.into()).unwrap()
+}
+
+#[wasm_bindgen_test]
+fn parses_into_plain_objects() {
+    let value = parse_value("{'name': 'test', \"items\": [1, 2,", JsValue::UNDEFINED).unwrap();
+    assert_eq!(get(&value, "name"), "test");
+
+    let items = get(&value, "items");
+    assert!(js_sys::Array::is_array(&items));
+    assert_eq!(get(&items, "length"), 2);
+    assert_eq!(get(&items, "1"), 2);
+}
+
+#[wasm_bindgen_test]
+fn keys_keep_their_order() {
+    let value = parse_value("{'b': 1, 'a': 2, 'c': 3}", JsValue::UNDEFINED).unwrap();
+    let keys = js_sys::Object::keys(value.unchecked_ref::<js_sys::Object>());
+    let keys: Vec<String> = keys.iter().map(|key| key.as_string().unwrap()).collect();
+    assert_eq!(keys, ["b", "a", "c"]);
+}
+
+#[wasm_bindgen_test]
+fn keeps_big_integers_exact() {
+    let value = parse_value(
+        r#"{"small": 42, "big": 9007199254740993}"#,
+        JsValue::UNDEFINED,
+    )
+    .unwrap();
+    assert_eq!(get(&value, "small"), 42);
+    let big = get(&value, "big").dyn_into::<BigInt>().unwrap();
+    assert_eq!(String::from(big.to_string(10).unwrap()), "9007199254740993");
+}
+
+#[wasm_bindgen_test]
+fn applies_options() {
+    let options = js_sys::Object::new();
+    Reflect::set(&options, &"allowComments".into(), &false.into()).unwrap();
+    let parser = Parser::new(options.into()).unwrap();
+    assert!(parser.parse_value("{\"a\": 1, // note\n}").is_err());
+
+    let options = js_sys::Object::new();
+    Reflect::set(&options, &"maxDepth".into(), &1.into()).unwrap();
+    let error = parse_value("[[1]]", options.into()).unwrap_err();
+    assert_eq!(get(&error, "kind"), "NestingTooDeep");
+}
+
+#[wasm_bindgen_test]
+fn errors_carry_kind_and_position() {
+    let options = js_sys::Object::new();
+    Reflect::set(&options, &"strictMode".into(), &true.into()).unwrap();
+    let error = parse_value(r#"{"name": "test", "value":"#, options.into()).unwrap_err();
+    assert!(error.is_instance_of::<js_sys::Error>());
+    assert_eq!(get(&error, "kind"), "StrictModeViolation");
+    assert_eq!(get(&error, "pos"), 25);
+    assert_eq!(get(&error, "column"), 26);
+    assert_eq!(get(&error, "path"), "/value");
+}
+
+#[wasm_bindgen_test]
+fn closes_truncated_scopes() {
+    let closed =
+        aggressively_close_scopes(r#"{"items": [1, {"a": "b"#, JsValue::UNDEFINED).unwrap();
+    assert_eq!(closed, r#"{"items": [1, {"a": "b"}]}"#);
+}